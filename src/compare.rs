@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+// 木やヒープの順序を与える比較関数
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, left: &T, right: &T) -> Ordering;
+}
+
+// Ord による順序
+#[derive(Clone, Copy, Default)]
+pub struct Natural;
+
+impl<T: Ord + ?Sized> Compare<T> for Natural {
+    fn compare(&self, left: &T, right: &T) -> Ordering {
+        left.cmp(right)
+    }
+}
+
+// 逆順
+// Rev(Natural) で降順になる
+#[derive(Clone, Copy, Default)]
+pub struct Rev<C>(pub C);
+
+impl<T: ?Sized, C: Compare<T>> Compare<T> for Rev<C> {
+    fn compare(&self, left: &T, right: &T) -> Ordering {
+        self.0.compare(right, left)
+    }
+}
+
+// 値から取り出したキーの Ord による順序
+// キーが等しい値は同じものとみなされる
+pub struct Key<F, K> {
+    f: F,
+    _marker: PhantomData<fn() -> K>,
+}
+
+impl<F, K> Key<F, K> {
+    pub fn new(f: F) -> Self {
+        Key {
+            f,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Clone, K> Clone for Key<F, K> {
+    fn clone(&self) -> Self {
+        Key::new(self.f.clone())
+    }
+}
+
+impl<T: ?Sized, K: Ord, F: Fn(&T) -> K> Compare<T> for Key<F, K> {
+    fn compare(&self, left: &T, right: &T) -> Ordering {
+        (self.f)(left).cmp(&(self.f)(right))
+    }
+}
+
+// 比較関数をそのまま使う
+impl<T: ?Sized, F: Fn(&T, &T) -> Ordering> Compare<T> for F {
+    fn compare(&self, left: &T, right: &T) -> Ordering {
+        self(left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, Natural, Rev};
+    use crate::red_black_tree::RedBlackTree;
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn test_red_black_tree_rev() {
+        let mut tree: RedBlackTree<i32, (), Rev<Natural>> = RedBlackTree::default();
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            assert!(tree.insert(x));
+            tree.check().unwrap();
+        }
+        assert!(tree.iter().copied().eq((0..100).rev()));
+        assert_eq!(tree.first(), Some(&99));
+        assert_eq!(tree.ceiling(&50), Some(&50));
+        assert_eq!(tree.successor(&50), Some(&49));
+        assert_eq!(tree.rank(&90), 9);
+        let (start, end) = (60, 50);
+        assert!(tree.range(start..=end).copied().eq((50..=60).rev()));
+        for &x in &v {
+            assert!(tree.remove(&x));
+            tree.check().unwrap();
+        }
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_red_black_tree_key_and_closure() {
+        let words = ["pear", "fig", "banana", "kiwi", "apple", "cherry"];
+        let mut by_len = RedBlackTree::with_comparator(Key::new(|s: &&str| s.len()));
+        for &w in &words {
+            by_len.insert(w);
+        }
+        // 長さが等しいものは最初に挿入したものだけ残る
+        assert_eq!(
+            by_len.iter().copied().collect::<Vec<_>>(),
+            ["fig", "pear", "apple", "banana"]
+        );
+        assert!(by_len.contains(&"plum"));
+
+        let mut by_last = RedBlackTree::with_comparator(|a: &&str, b: &&str| {
+            a.chars().rev().cmp(b.chars().rev())
+        });
+        for &w in &words {
+            by_last.insert(w);
+        }
+        by_last.check().unwrap();
+        assert_eq!(
+            by_last.iter().copied().collect::<Vec<_>>(),
+            ["banana", "apple", "fig", "kiwi", "pear", "cherry"]
+        );
+    }
+}
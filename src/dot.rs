@@ -0,0 +1,117 @@
+// Graphviz の DOT 形式で木を書き出す
+// 節には出力順に n0, n1, ... と名前を付ける
+use std::fmt::{self, Write};
+
+pub(crate) struct Dot {
+    out: String,
+    count: usize,
+    show_nil: bool,
+}
+
+impl Dot {
+    pub(crate) fn new(name: &str, show_nil: bool) -> Self {
+        let mut out = String::new();
+        writeln!(out, "digraph {} {{", name).unwrap();
+        writeln!(out, "    node [shape=circle];").unwrap();
+        Dot {
+            out,
+            count: 0,
+            show_nil,
+        }
+    }
+
+    // color を指定すると塗りつぶす
+    pub(crate) fn node<T: fmt::Debug>(&mut self, value: &T, color: Option<&str>) -> usize {
+        let id = self.count;
+        self.count += 1;
+        // ラベルの中の " と \ はエスケープする
+        let label = format!("{:?}", value)
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
+        match color {
+            Some(color) => writeln!(
+                self.out,
+                "    n{} [label=\"{}\", style=filled, fillcolor={}, fontcolor=white];",
+                id, label, color
+            ),
+            None => writeln!(self.out, "    n{} [label=\"{}\"];", id, label),
+        }
+        .unwrap();
+        id
+    }
+
+    // null の葉は小さな黒い四角で表す
+    // 表示しない設定なら None
+    pub(crate) fn nil(&mut self) -> Option<usize> {
+        if !self.show_nil {
+            return None;
+        }
+        let id = self.count;
+        self.count += 1;
+        writeln!(
+            self.out,
+            "    n{} [label=\"\", shape=box, width=0.2, height=0.2, style=filled, fillcolor=black];",
+            id
+        )
+        .unwrap();
+        Some(id)
+    }
+
+    pub(crate) fn edge(&mut self, from: Option<usize>, to: Option<usize>, label: &str) {
+        if let (Some(from), Some(to)) = (from, to) {
+            writeln!(self.out, "    n{} -> n{} [label=\"{}\"];", from, to, label).unwrap();
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.out.push_str("}\n");
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::heaps::meldable_heap::MeldableHeap;
+    use crate::red_black_tree::RedBlackTree;
+    use crate::red_black_tree_v1;
+
+    fn count(dot: &str, pattern: &str) -> usize {
+        dot.matches(pattern).count()
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut tree = RedBlackTree::new();
+        let mut v1 = red_black_tree_v1::RedBlackTree::new();
+        let mut heap = MeldableHeap::new();
+        for x in 0..10 {
+            tree.insert(x);
+            v1.insert(x);
+            heap.insert(x);
+        }
+        for dot in &[tree.to_dot(), v1.to_dot(), heap.to_dot()] {
+            assert!(dot.starts_with("digraph "));
+            assert!(dot.ends_with("}\n"));
+            assert_eq!(count(dot, "[label=\""), 10 + 9);
+            assert_eq!(count(dot, " -> "), 9);
+            assert_eq!(count(dot, "[label=\"L\"]") + count(dot, "[label=\"R\"]"), 9);
+        }
+        let dot = tree.to_dot();
+        assert!(dot.contains("fillcolor=red"));
+        assert!(dot.contains("n0 [label=\"3\", style=filled, fillcolor=black"));
+
+        // 節が n 個なら null の葉は n + 1 個
+        for dot in &[tree.to_dot_with_nil(), v1.to_dot_with_nil()] {
+            assert_eq!(count(dot, "shape=box"), 11);
+            assert_eq!(count(dot, " -> "), 20);
+        }
+        assert_eq!(
+            RedBlackTree::<i32>::new().to_dot(),
+            "digraph RedBlackTree {\n    node [shape=circle];\n}\n"
+        );
+
+        let mut strings = RedBlackTree::new();
+        strings.insert("a\"b".to_string());
+        assert!(strings.to_dot().contains(r#"[label="\"a\\\"b\"""#));
+    }
+}
@@ -0,0 +1,467 @@
+// std のコレクションをモデルとした差分テスト
+// シード付きの乱数で操作列を作り、各操作の結果と不変条件を確かめる
+// 失敗したら操作列を縮めて最小の再現例を報告する
+// FUZZ_SEED でシードを、FUZZ_CASES で試行回数を指定できる
+// 乱択を使う構造も固定のシードで作るので、同じ操作列なら同じ結果になる
+
+use crate::hash_tables::linear_hash_map::LinearHashMap;
+use crate::hash_tables::linear_hash_table::LinearHashTable;
+use crate::heaps::meldable_heap::MeldableHeap;
+use crate::red_black_tree::{self, RedBlackTreeMap, Sum};
+use crate::red_black_tree_v1;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+trait Model {
+    type Op: Clone + fmt::Debug;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> Self::Op;
+
+    // 操作列を最初から適用し、最初に食い違った箇所を返す
+    fn run(ops: &[Self::Op]) -> Result<(), String>;
+}
+
+fn env_or(key: &str, default: u64) -> u64 {
+    env::var(key)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+// panic も失敗として扱う
+fn run_catch<M: Model>(ops: &[M::Op]) -> Result<(), String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| M::run(ops))) {
+        Ok(result) => result,
+        Err(e) => Err(match e.downcast_ref::<&str>() {
+            Some(s) => format!("panicked: {}", s),
+            None => match e.downcast_ref::<String>() {
+                Some(s) => format!("panicked: {}", s),
+                None => "panicked".to_string(),
+            },
+        }),
+    }
+}
+
+thread_local! {
+    // 縮めている間は、このスレッドの panic の出力を抑える
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+// panic の hook はプロセス全体で共有され、他のテストも並行して動くので、
+// 差し替えるのは一度だけにして、QUIET なスレッドの出力だけを捨てる
+fn install_quiet_hook() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+// 連続する区間を取り除いて失敗し続けるものを探す (delta debugging の簡易版)
+fn shrink<M: Model>(mut ops: Vec<M::Op>) -> Vec<M::Op> {
+    let mut chunk = ops.len() / 2;
+    while chunk > 0 {
+        let mut i = 0;
+        while i + chunk <= ops.len() {
+            let mut candidate = ops[..i].to_vec();
+            candidate.extend_from_slice(&ops[i + chunk..]);
+            if run_catch::<M>(&candidate).is_err() {
+                ops = candidate;
+            } else {
+                i += chunk;
+            }
+        }
+        chunk /= 2;
+    }
+    ops
+}
+
+fn fuzz<M: Model>(name: &str, max_len: usize) {
+    let seed = env_or("FUZZ_SEED", 0x5eed);
+    let cases = env_or("FUZZ_CASES", 100);
+    for case in 0..cases {
+        let mut rng = StdRng::seed_from_u64(seed.wrapping_add(case));
+        let len = rng.gen_range(0, max_len + 1);
+        let ops = (0..len).map(|_| M::gen_op(&mut rng)).collect::<Vec<_>>();
+        if run_catch::<M>(&ops).is_err() {
+            // 縮める間の panic の出力は邪魔なので抑える
+            install_quiet_hook();
+            QUIET.with(|quiet| quiet.set(true));
+            let ops = shrink::<M>(ops);
+            QUIET.with(|quiet| quiet.set(false));
+            let err = run_catch::<M>(&ops).unwrap_err();
+            panic!(
+                "{}: FUZZ_SEED={} case {} failed: {}\nminimal ops: {:?}",
+                name, seed, case, err, ops
+            );
+        }
+    }
+}
+
+fn check(step: usize, result: Result<(), &str>) -> Result<(), String> {
+    result.map_err(|e| format!("step {}: invariant violated: {}", step, e))
+}
+
+fn expect<T: PartialEq + fmt::Debug>(step: usize, actual: T, expected: T) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "step {}: expected {:?}, got {:?}",
+            step, expected, actual
+        ))
+    }
+}
+
+// 値域を狭くして重複や削除の当たりを増やす
+const MAX: i32 = 64;
+
+#[derive(Clone, Debug)]
+enum SetOp {
+    Insert(i32),
+    Remove(i32),
+    Contains(i32),
+}
+
+fn gen_set_op<R: Rng>(rng: &mut R) -> SetOp {
+    let x = rng.gen_range(0, MAX);
+    match rng.gen_range(0, 5) {
+        0 | 1 => SetOp::Insert(x),
+        2 | 3 => SetOp::Remove(x),
+        _ => SetOp::Contains(x),
+    }
+}
+
+struct RedBlackTreeModel;
+
+impl Model for RedBlackTreeModel {
+    type Op = SetOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> SetOp {
+        gen_set_op(rng)
+    }
+
+    fn run(ops: &[SetOp]) -> Result<(), String> {
+        // 回転や修正で集約が保たれることも確かめる
+        let mut tree: red_black_tree::RedBlackTree<i32, Sum> = Default::default();
+        let mut set = BTreeSet::new();
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                SetOp::Insert(x) => expect(step, tree.insert(x), set.insert(x))?,
+                SetOp::Remove(x) => expect(step, tree.remove(&x), set.remove(&x))?,
+                SetOp::Contains(x) => expect(step, tree.contains(&x), set.contains(&x))?,
+            }
+            check(step, tree.check_summary())?;
+            expect(step, tree.len(), set.len())?;
+            expect(step, tree.first(), set.iter().next())?;
+            expect(step, tree.last(), set.iter().next_back())?;
+        }
+        expect(
+            ops.len(),
+            tree.iter().collect(),
+            set.iter().collect::<Vec<_>>(),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+enum PersistentOp {
+    Set(SetOp),
+    // 現在の版を取っておき、最後に変わっていないことを確かめる
+    Snapshot,
+}
+
+struct RedBlackTreeV1Model;
+
+impl Model for RedBlackTreeV1Model {
+    type Op = PersistentOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> PersistentOp {
+        if rng.gen_range(0, 10) == 0 {
+            PersistentOp::Snapshot
+        } else {
+            PersistentOp::Set(gen_set_op(rng))
+        }
+    }
+
+    fn run(ops: &[PersistentOp]) -> Result<(), String> {
+        let mut tree = red_black_tree_v1::RedBlackTree::new();
+        let mut set = BTreeSet::new();
+        let mut snapshots = vec![];
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                PersistentOp::Set(SetOp::Insert(x)) => expect(step, tree.insert(x), set.insert(x))?,
+                PersistentOp::Set(SetOp::Remove(x)) => {
+                    expect(step, tree.remove(&x), set.remove(&x))?
+                }
+                PersistentOp::Set(SetOp::Contains(x)) => {
+                    expect(step, tree.contains(&x), set.contains(&x))?
+                }
+                PersistentOp::Snapshot => snapshots.push((step, tree.clone(), set.clone())),
+            }
+            check(step, tree.check())?;
+            expect(step, tree.len(), set.len())?;
+        }
+        for (step, tree, set) in &snapshots {
+            expect(*step, tree.iter().collect(), set.iter().collect::<Vec<_>>())?;
+        }
+        expect(
+            ops.len(),
+            tree.iter().collect(),
+            set.iter().collect::<Vec<_>>(),
+        )
+    }
+}
+
+struct LinearHashTableModel;
+
+impl Model for LinearHashTableModel {
+    type Op = SetOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> SetOp {
+        gen_set_op(rng)
+    }
+
+    fn run(ops: &[SetOp]) -> Result<(), String> {
+        let mut table = LinearHashTable::with_seed(0);
+        let mut set = HashSet::new();
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                SetOp::Insert(x) => expect(step, table.insert(x), set.insert(x))?,
+                SetOp::Remove(x) => expect(step, table.remove(&x), set.remove(&x))?,
+                SetOp::Contains(x) => expect(step, table.get(&x), set.get(&x))?,
+            }
+            check(step, table.check())?;
+            expect(step, table.len(), set.len())?;
+        }
+        for x in 0..MAX {
+            expect(ops.len(), table.get(&x), set.get(&x))?;
+        }
+        let mut values = table.into_iter().collect::<Vec<_>>();
+        let mut expected = set.into_iter().collect::<Vec<_>>();
+        values.sort();
+        expected.sort();
+        expect(ops.len(), values, expected)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum MapOp {
+    Insert(i32, i32),
+    Remove(i32),
+    Get(i32),
+    // entry(k).or_insert(v) に 1 を足す
+    Entry(i32, i32),
+}
+
+fn gen_map_op<R: Rng>(rng: &mut R) -> MapOp {
+    let k = rng.gen_range(0, MAX);
+    let v = rng.gen_range(0, 1000);
+    match rng.gen_range(0, 7) {
+        0 | 1 => MapOp::Insert(k, v),
+        2 | 3 => MapOp::Remove(k),
+        4 => MapOp::Get(k),
+        _ => MapOp::Entry(k, v),
+    }
+}
+
+struct RedBlackTreeMapModel;
+
+impl Model for RedBlackTreeMapModel {
+    type Op = MapOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> MapOp {
+        gen_map_op(rng)
+    }
+
+    fn run(ops: &[MapOp]) -> Result<(), String> {
+        let mut map = RedBlackTreeMap::new();
+        let mut model = BTreeMap::new();
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                MapOp::Insert(k, v) => expect(step, map.insert(k, v), model.insert(k, v))?,
+                MapOp::Remove(k) => expect(step, map.remove(&k), model.remove(&k))?,
+                MapOp::Get(k) => expect(step, map.get(&k), model.get(&k))?,
+                MapOp::Entry(k, v) => {
+                    let x = map.entry(k).or_insert(v);
+                    *x += 1;
+                    let y = model.entry(k).or_insert(v);
+                    *y += 1;
+                    expect(step, *x, *y)?;
+                }
+            }
+            check(step, map.check())?;
+            expect(step, map.len(), model.len())?;
+        }
+        for k in 0..MAX {
+            expect(ops.len(), map.get(&k), model.get(&k))?;
+        }
+        Ok(())
+    }
+}
+
+struct LinearHashMapModel;
+
+impl Model for LinearHashMapModel {
+    type Op = MapOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> MapOp {
+        gen_map_op(rng)
+    }
+
+    fn run(ops: &[MapOp]) -> Result<(), String> {
+        let mut map = LinearHashMap::with_seed(0);
+        let mut model = HashMap::new();
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                MapOp::Insert(k, v) => expect(step, map.insert(k, v), model.insert(k, v))?,
+                MapOp::Remove(k) => expect(step, map.remove(&k), model.remove(&k))?,
+                MapOp::Get(k) => expect(step, map.get(&k), model.get(&k))?,
+                MapOp::Entry(k, v) => {
+                    let x = map.entry(k).or_insert(v);
+                    *x += 1;
+                    let y = model.entry(k).or_insert(v);
+                    *y += 1;
+                    expect(step, *x, *y)?;
+                }
+            }
+            check(step, map.check())?;
+            expect(step, map.len(), model.len())?;
+        }
+        for k in 0..MAX {
+            expect(ops.len(), map.get(&k), model.get(&k))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+enum HeapOp {
+    Insert(i32),
+    Pop,
+    Append(Vec<i32>),
+}
+
+struct MeldableHeapModel;
+
+impl Model for MeldableHeapModel {
+    type Op = HeapOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> HeapOp {
+        match rng.gen_range(0, 10) {
+            0..=4 => HeapOp::Insert(rng.gen_range(0, MAX)),
+            5..=8 => HeapOp::Pop,
+            _ => {
+                let n = rng.gen_range(0, 8);
+                HeapOp::Append((0..n).map(|_| rng.gen_range(0, MAX)).collect())
+            }
+        }
+    }
+
+    fn run(ops: &[HeapOp]) -> Result<(), String> {
+        let mut heap = MeldableHeap::with_seed(0);
+        let mut model = BinaryHeap::new();
+        for (step, op) in ops.iter().enumerate() {
+            match op {
+                HeapOp::Insert(x) => {
+                    heap.insert(*x);
+                    model.push(Reverse(*x));
+                }
+                HeapOp::Pop => expect(step, heap.pop(), model.pop().map(|Reverse(x)| x))?,
+                HeapOp::Append(xs) => {
+                    let mut other = MeldableHeap::with_seed(1);
+                    for &x in xs {
+                        other.insert(x);
+                        model.push(Reverse(x));
+                    }
+                    heap.append(&mut other);
+                    expect(step, other.len(), 0)?;
+                }
+            }
+            check(step, heap.check())?;
+            expect(step, heap.len(), model.len())?;
+            expect(step, heap.is_empty(), model.is_empty())?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn fuzz_red_black_tree() {
+    fuzz::<RedBlackTreeModel>("red_black_tree", 300);
+}
+
+#[test]
+fn fuzz_red_black_tree_v1() {
+    fuzz::<RedBlackTreeV1Model>("red_black_tree_v1", 300);
+}
+
+#[test]
+fn fuzz_linear_hash_table() {
+    fuzz::<LinearHashTableModel>("linear_hash_table", 300);
+}
+
+#[test]
+fn fuzz_red_black_tree_map() {
+    fuzz::<RedBlackTreeMapModel>("red_black_tree_map", 300);
+}
+
+#[test]
+fn fuzz_linear_hash_map() {
+    fuzz::<LinearHashMapModel>("linear_hash_map", 300);
+}
+
+#[test]
+fn fuzz_meldable_heap() {
+    fuzz::<MeldableHeapModel>("meldable_heap", 300);
+}
+
+// 壊れた実装を与えて、縮小が最小の再現例を見つけることを確かめる
+struct BrokenModel;
+
+impl Model for BrokenModel {
+    type Op = SetOp;
+
+    fn gen_op<R: Rng>(rng: &mut R) -> SetOp {
+        gen_set_op(rng)
+    }
+
+    // 7 を入れた後に 3 を消すと食い違う
+    fn run(ops: &[SetOp]) -> Result<(), String> {
+        let mut seven = false;
+        for (step, op) in ops.iter().enumerate() {
+            match *op {
+                SetOp::Insert(7) => seven = true,
+                SetOp::Remove(3) if seven => return Err(format!("step {}: broken", step)),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fuzz_shrink() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut ops = (0..200)
+        .map(|_| BrokenModel::gen_op(&mut rng))
+        .collect::<Vec<_>>();
+    ops.insert(50, SetOp::Insert(7));
+    ops.push(SetOp::Remove(3));
+    assert!(BrokenModel::run(&ops).is_err());
+    let ops = shrink::<BrokenModel>(ops);
+    assert_eq!(ops.len(), 2);
+    assert!(matches!(ops[0], SetOp::Insert(7)));
+    assert!(matches!(ops[1], SetOp::Remove(3)));
+}
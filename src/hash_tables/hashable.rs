@@ -0,0 +1,210 @@
+use std::hash::{BuildHasher, Hash};
+
+pub trait Hashable {
+    fn hash_code(&self) -> usize;
+}
+
+// std::hash::Hash を使って hash_code を求める
+// Hashable を自前の型に実装するときにも使える
+pub fn hash_with<T: Hash + ?Sized, S: BuildHasher>(value: &T, build: &S) -> usize {
+    build.hash_one(value) as usize
+}
+
+// 表がハッシュ値を求める方法で、表ごとに一つ持たせる
+// 既定の ByHashable は Hashable を使い、std::hash::BuildHasher なら std::hash::Hash を使う
+// (RandomState のように表ごとに状態の異なるものでもよい)
+// Borrow で引くときは Q のハッシュ値を求めるので、T と Q で同じ値になる必要がある
+pub trait BuildHashCode<Q: ?Sized> {
+    fn hash_code(&self, x: &Q) -> usize;
+}
+
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ByHashable;
+
+impl<Q: Hashable + ?Sized> BuildHashCode<Q> for ByHashable {
+    fn hash_code(&self, x: &Q) -> usize {
+        x.hash_code()
+    }
+}
+
+impl<Q: Hash + ?Sized, S: BuildHasher> BuildHashCode<Q> for S {
+    fn hash_code(&self, x: &Q) -> usize {
+        hash_with(x, self)
+    }
+}
+
+// 整数はそのまま使う (混ぜるのは表の側で行う)
+macro_rules! impl_hashable_int {
+    ($($t:ty)*) => {
+        $(
+            impl Hashable for $t {
+                fn hash_code(&self) -> usize {
+                    *self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_hashable_int!(u8 u16 u32 u64 usize i8 i16 i32 i64 isize bool char);
+
+// usize に収まらない分は上位と下位を畳み込む
+impl Hashable for u128 {
+    fn hash_code(&self) -> usize {
+        (*self as u64 ^ (*self >> 64) as u64) as usize
+    }
+}
+
+impl Hashable for i128 {
+    fn hash_code(&self) -> usize {
+        (*self as u128).hash_code()
+    }
+}
+
+// 要素の hash_code を多項式で繋げる
+fn combine<I: IntoIterator<Item = usize>>(codes: I) -> usize {
+    codes
+        .into_iter()
+        .fold(0usize, |h, c| h.wrapping_mul(31).wrapping_add(c))
+}
+
+// Borrow で引けるように、String と str、Vec と配列とスライスは同じ値になるようにする
+impl Hashable for str {
+    fn hash_code(&self) -> usize {
+        combine(self.bytes().map(|b| b as usize))
+    }
+}
+
+impl Hashable for String {
+    fn hash_code(&self) -> usize {
+        self.as_str().hash_code()
+    }
+}
+
+impl<T: Hashable> Hashable for [T] {
+    fn hash_code(&self) -> usize {
+        combine(self.iter().map(Hashable::hash_code))
+    }
+}
+
+impl<T: Hashable, const N: usize> Hashable for [T; N] {
+    fn hash_code(&self) -> usize {
+        self[..].hash_code()
+    }
+}
+
+impl<T: Hashable> Hashable for Vec<T> {
+    fn hash_code(&self) -> usize {
+        self[..].hash_code()
+    }
+}
+
+impl<T: Hashable + ?Sized> Hashable for &T {
+    fn hash_code(&self) -> usize {
+        (**self).hash_code()
+    }
+}
+
+impl<T: Hashable + ?Sized> Hashable for Box<T> {
+    fn hash_code(&self) -> usize {
+        (**self).hash_code()
+    }
+}
+
+impl Hashable for () {
+    fn hash_code(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! impl_hashable_tuple {
+    ($($t:ident)+) => {
+        impl<$($t: Hashable),+> Hashable for ($($t,)+) {
+            #[allow(non_snake_case)]
+            fn hash_code(&self) -> usize {
+                let ($($t,)+) = self;
+                combine([$($t.hash_code()),+])
+            }
+        }
+    };
+}
+
+impl_hashable_tuple!(A);
+impl_hashable_tuple!(A B);
+impl_hashable_tuple!(A B C);
+impl_hashable_tuple!(A B C D);
+impl_hashable_tuple!(A B C D E);
+impl_hashable_tuple!(A B C D E F);
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_with, Hashable};
+    use crate::hash_tables::linear_hash_map::LinearHashMap;
+    use crate::hash_tables::linear_hash_table::LinearHashTable;
+    use std::collections::hash_map::{DefaultHasher, RandomState};
+    use std::hash::BuildHasherDefault;
+
+    #[test]
+    fn test_hashable_borrow() {
+        assert_eq!("abc".hash_code(), "abc".to_string().hash_code());
+        assert_eq!([1, 2, 3].hash_code(), vec![1, 2, 3].hash_code());
+        assert_eq!([1, 2, 3][..].hash_code(), vec![1, 2, 3].hash_code());
+        assert_ne!((1, 2).hash_code(), (2, 1).hash_code());
+        assert_ne!((1u128 << 64).hash_code(), 0);
+
+        let mut map = LinearHashMap::new();
+        map.insert(vec![1, 2], "a");
+        map.insert(vec![3], "b");
+        assert_eq!(map.get(&[1, 2][..]), Some(&"a"));
+        let mut map = LinearHashMap::new();
+        map.insert((1, "x".to_string()), 1);
+        map.insert((1, "y".to_string()), 2);
+        assert_eq!(map.get(&(1, "y".to_string())), Some(&2));
+        map.check().unwrap();
+    }
+
+    #[derive(Clone, PartialEq, Eq, Hash, Debug)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn test_build_hasher() {
+        // RandomState は表ごとに一つ作って持たせる
+        let mut table = LinearHashTable::with_hasher(RandomState::new());
+        for x in 0..100 {
+            assert!(table.insert(Point { x, y: -x }));
+        }
+        assert!(!table.insert(Point { x: 3, y: -3 }));
+        assert!(table.remove(&Point { x: 3, y: -3 }));
+        assert!(!table.remove(&Point { x: 3, y: 3 }));
+        assert_eq!(table.len(), 99);
+        table.check().unwrap();
+
+        // String と str は std::hash::Hash でも同じハッシュ値になるので、&str で引ける
+        let mut table = LinearHashTable::with_hasher(RandomState::new());
+        table.insert("abc".to_string());
+        assert_eq!(table.get("abc").map(String::as_str), Some("abc"));
+        assert!(table.contains("abc"));
+        assert!(!table.contains("abd"));
+        let mut map = LinearHashMap::with_hasher(RandomState::new());
+        map.insert("a".to_string(), 1);
+        *map.entry("b".to_string()).or_insert(0) += 2;
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("b"), Some(&2));
+        map.check().unwrap();
+
+        // 自前の型に hash_with で実装する
+        #[derive(PartialEq, Eq, Hash)]
+        struct Name(String);
+        impl Hashable for Name {
+            fn hash_code(&self) -> usize {
+                hash_with(self, &BuildHasherDefault::<DefaultHasher>::default())
+            }
+        }
+        let mut map = LinearHashMap::new();
+        map.insert(Name("a".to_string()), 1);
+        assert_eq!(map.get(&Name("a".to_string())), Some(&1));
+    }
+}
@@ -0,0 +1,406 @@
+use super::hashable::{BuildHashCode, ByHashable, Hashable};
+use super::linear_hash_table::{LinearHashTable, Tabulation};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::fmt;
+use std::mem;
+
+// (キー, 値) の組を LinearHashTable に載せ、キーのみでハッシュ・比較する
+struct Bucket<K, V> {
+    key: K,
+    value: V,
+}
+
+// 表には組のハッシュ値をキーの S で求めさせる
+struct BucketHash<S>(S);
+
+impl<K, V, S: BuildHashCode<K>> BuildHashCode<Bucket<K, V>> for BucketHash<S> {
+    fn hash_code(&self, b: &Bucket<K, V>) -> usize {
+        self.0.hash_code(&b.key)
+    }
+}
+
+impl<K: PartialEq, V> PartialEq for Bucket<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq, V> Eq for Bucket<K, V> {}
+
+pub struct LinearHashMap<K, V, S = ByHashable> {
+    table: LinearHashTable<Bucket<K, V>, BucketHash<S>>,
+}
+
+impl<K: Hashable + Eq, V> LinearHashMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::with_rng_and_tabulation(rng, Tabulation::Simple)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_tabulation(tabulation: Tabulation) -> Self {
+        Self::with_rng_and_tabulation(&mut rand::thread_rng(), tabulation)
+    }
+
+    pub fn with_rng_and_tabulation<R: Rng + ?Sized>(rng: &mut R, tabulation: Tabulation) -> Self {
+        Self::with_rng_tabulation_and_hasher(rng, tabulation, ByHashable)
+    }
+}
+
+impl<K: Eq, V, S: BuildHashCode<K>> LinearHashMap<K, V, S> {
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_rng_tabulation_and_hasher(&mut rand::thread_rng(), Tabulation::Simple, hasher)
+    }
+
+    pub fn with_rng_tabulation_and_hasher<R: Rng + ?Sized>(
+        rng: &mut R,
+        tabulation: Tabulation,
+        hasher: S,
+    ) -> Self {
+        LinearHashMap {
+            table: LinearHashTable::with_rng_tabulation_and_hasher(
+                rng,
+                tabulation,
+                BucketHash(hasher),
+            ),
+        }
+    }
+
+    fn position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        let code = self.table.hasher().0.hash_code(key);
+        self.table.position(code, |b| b.key.borrow() == key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        self.position(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        let i = self.position(key)?;
+        Some(&self.table.value_at(i).value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        let i = self.position(key)?;
+        Some(&mut self.table.value_at_mut(i).value)
+    }
+
+    // 既にキーがあれば値を置き換え、古い値を返す
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.position(&key) {
+            Some(i) => Some(mem::replace(&mut self.table.value_at_mut(i).value, value)),
+            None => {
+                self.table.insert_new(Bucket { key, value });
+                None
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        let i = self.position(key)?;
+        Some(self.table.remove_at(i).value)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        match self.position(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.table.hasher().0
+    }
+
+    pub fn check(&self) -> Result<(), &str> {
+        self.table.check()
+    }
+}
+
+impl<K: Eq, V, S: BuildHashCode<K> + Default> Default for LinearHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for LinearHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.table.iter().map(|b| (&b.key, &b.value)))
+            .finish()
+    }
+}
+
+// (キー, 値) の組の列として読み書きする (順序は不定)
+#[cfg(feature = "serde")]
+impl<K: Serialize, V: Serialize, H> Serialize for LinearHashMap<K, V, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.table.iter().map(|b| (&b.key, &b.value)))
+    }
+}
+
+// 同じキーが複数あれば後のものを残す
+#[cfg(feature = "serde")]
+impl<'de, K, V, S> Deserialize<'de> for LinearHashMap<K, V, S>
+where
+    K: Eq + Deserialize<'de>,
+    V: Deserialize<'de>,
+    S: BuildHashCode<K> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Vec::deserialize(deserializer)?;
+        let mut map = Self::default();
+        map.table.grow_for(v.len());
+        for (key, value) in v {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+// std::collections::hash_map::Entry に倣ったもの
+// 間に他の操作は入らないので、Occupied は表の位置を持っておけばよい
+pub enum Entry<'a, K, V, S = ByHashable> {
+    Vacant(VacantEntry<'a, K, V, S>),
+    Occupied(OccupiedEntry<'a, K, V, S>),
+}
+
+pub struct VacantEntry<'a, K, V, S = ByHashable> {
+    map: &'a mut LinearHashMap<K, V, S>,
+    key: K,
+}
+
+pub struct OccupiedEntry<'a, K, V, S = ByHashable> {
+    map: &'a mut LinearHashMap<K, V, S>,
+    index: usize,
+}
+
+impl<'a, K: Eq, V, S: BuildHashCode<K>> Entry<'a, K, V, S> {
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Vacant(e) => e.insert(default),
+            Entry::Occupied(e) => e.into_mut(),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(e) => e.insert(default()),
+            Entry::Occupied(e) => e.into_mut(),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Vacant(e) => Entry::Vacant(e),
+            Entry::Occupied(mut e) => {
+                f(e.get_mut());
+                Entry::Occupied(e)
+            }
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(e) => e.key(),
+            Entry::Occupied(e) => e.key(),
+        }
+    }
+}
+
+impl<'a, K: Eq, V: Default, S: BuildHashCode<K>> Entry<'a, K, V, S> {
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Eq, V, S: BuildHashCode<K>> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let i = self.map.table.insert_new(Bucket {
+            key: self.key,
+            value,
+        });
+        &mut self.map.table.value_at_mut(i).value
+    }
+}
+
+impl<'a, K: Eq, V, S: BuildHashCode<K>> OccupiedEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.map.table.value_at(self.index).key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.table.value_at(self.index).value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.table.value_at_mut(self.index).value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.table.value_at_mut(self.index).value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.map.table.remove_at(self.index).value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, LinearHashMap, Tabulation};
+    use rand::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_linear_hash_map() {
+        let mut map = LinearHashMap::new();
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.insert(2, "two"), None);
+        assert_eq!(map.insert(1, "uno"), Some("one"));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&"uno"));
+        assert!(map.contains_key(&2));
+        *map.get_mut(&2).unwrap() = "dos";
+        assert_eq!(map.remove(&2), Some("dos"));
+        assert_eq!(map.remove(&2), None);
+        assert!(!map.contains_key(&2));
+        assert_eq!(map.len(), 1);
+        map.check().unwrap();
+
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut map = LinearHashMap::with_rng_and_tabulation(&mut rng, Tabulation::Twisted);
+        let mut model = HashMap::new();
+        for _ in 0..5000 {
+            let k = rng.gen_range(0, 200);
+            let v = rng.gen_range(0, 1000);
+            match rng.gen_range(0, 3) {
+                0 => assert_eq!(map.insert(k, v), model.insert(k, v)),
+                1 => assert_eq!(map.remove(&k), model.remove(&k)),
+                _ => assert_eq!(map.get(&k), model.get(&k)),
+            }
+            map.check().unwrap();
+            assert_eq!(map.len(), model.len());
+        }
+    }
+
+    #[test]
+    fn test_linear_hash_map_entry() {
+        let mut count: LinearHashMap<String, usize> = LinearHashMap::new();
+        for word in "a b a c b a".split(' ') {
+            *count.entry(word.to_string()).or_insert(0) += 1;
+        }
+        assert_eq!(count.get("a"), Some(&3));
+        assert_eq!(count.get("b"), Some(&2));
+        assert_eq!(count.get("c"), Some(&1));
+
+        count.entry("c".to_string()).and_modify(|v| *v *= 10);
+        assert_eq!(count.get("c"), Some(&10));
+        assert_eq!(*count.entry("d".to_string()).or_default(), 0);
+        assert_eq!(count.entry("e".to_string()).key(), "e");
+
+        match count.entry("a".to_string()) {
+            Entry::Occupied(mut e) => {
+                assert_eq!(e.key(), "a");
+                assert_eq!(e.insert(5), 3);
+                assert_eq!(e.remove(), 5);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match count.entry("a".to_string()) {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Vacant(e) => {
+                assert_eq!(*e.insert(7), 7);
+            }
+        }
+        assert_eq!(count.get("a"), Some(&7));
+        assert_eq!(count.len(), 4);
+        count.check().unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_linear_hash_map_serde() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+        use serde_test::{assert_ser_tokens, Token};
+
+        let mut map = LinearHashMap::new();
+        map.insert(1, 2);
+        assert_ser_tokens(
+            &map,
+            &[
+                Token::Seq { len: Some(1) },
+                Token::Tuple { len: 2 },
+                Token::I32(1),
+                Token::I32(2),
+                Token::TupleEnd,
+                Token::SeqEnd,
+            ],
+        );
+
+        let pairs = vec![(2, 24), (1, 1), (2, 4)]
+            .into_iter()
+            .map(|(k, v)| SeqDeserializer::<_, Error>::new(vec![k, v].into_iter()));
+        let map = LinearHashMap::<i32, i32>::deserialize(SeqDeserializer::<_, Error>::new(pairs))
+            .unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(map.get(&2), Some(&4));
+    }
+}
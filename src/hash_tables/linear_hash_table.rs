@@ -1,13 +1,37 @@
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
 use std::fmt;
+use std::iter::FromIterator;
 use std::mem;
 
-pub struct LinearHashTable<T> {
+mod iter;
+
+pub use super::hashable::{BuildHashCode, ByHashable, Hashable};
+pub use iter::{Drain, IntoIter, Iter};
+
+// S は値から hash_code を求めるもの (既定では Hashable を使う)
+pub struct LinearHashTable<T, S = ByHashable> {
     t: Vec<Item<T>>,
-    n: usize, // 値の個数
-    q: usize, // null でない値の個数
-    d: usize, // t.len() == 1 << d
-    tab: [Vec<usize>; 4],
+    n: usize,     // 値の個数
+    q: usize,     // null でない値の個数
+    d: usize,     // t.len() == 1 << d
+    min_d: usize, // reserve で確保した大きさ (resize でもこれより縮めない)
+    tab: [Vec<usize>; 8],
+    twist: Option<[Vec<u8>; 7]>,
+    hasher: S,
+}
+
+// ハッシュ値の求め方
+// Simple は hash_code() の 8 バイトをそれぞれ表で引いて xor する
+// Twisted は先頭 7 バイトから求めた値で最後のバイトを変えてから引く
+// (Pătraşcu と Thorup の twisted tabulation)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tabulation {
+    Simple,
+    Twisted,
 }
 
 #[derive(PartialEq)]
@@ -17,43 +41,91 @@ enum Item<T> {
     Del,
 }
 
-pub trait Hashable {
-    fn hash_code(&self) -> usize;
-}
-
 impl<T: Hashable + Eq> LinearHashTable<T> {
     pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
+        Self::with_rng(&mut rand::thread_rng())
+    }
+
+    pub fn with_tabulation(tabulation: Tabulation) -> Self {
+        Self::with_rng_and_tabulation(&mut rand::thread_rng(), tabulation)
+    }
+
+    pub fn with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::with_rng_and_tabulation(rng, Tabulation::Simple)
+    }
+
+    pub fn with_rng_and_tabulation<R: Rng + ?Sized>(rng: &mut R, tabulation: Tabulation) -> Self {
+        Self::with_rng_tabulation_and_hasher(rng, tabulation, ByHashable)
+    }
+
+    // 再現性が必要なとき
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut table = Self::new();
+        table.reserve(capacity);
+        table
+    }
+}
+
+impl<T: Eq, S: BuildHashCode<T>> LinearHashTable<T, S> {
+    // std::collections::HashMap::with_hasher と同じく、hash_code の求め方を渡す
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_rng_tabulation_and_hasher(&mut rand::thread_rng(), Tabulation::Simple, hasher)
+    }
+
+    // 乱数は表の作成時にしか使わないので、生成器は保持しない
+    pub fn with_rng_tabulation_and_hasher<R: Rng + ?Sized>(
+        rng: &mut R,
+        tabulation: Tabulation,
+        hasher: S,
+    ) -> Self {
+        let tab = std::array::from_fn(|_| (0..256).map(|_| rng.gen()).collect());
+        let twist = match tabulation {
+            Tabulation::Simple => None,
+            Tabulation::Twisted => Some(std::array::from_fn(|_| {
+                (0..256).map(|_| rng.gen()).collect()
+            })),
+        };
         LinearHashTable {
             t: vec![Item::Null],
             n: 0,
             q: 0,
             d: 0,
-            tab: [
-                (0..256).map(|_| rng.gen()).collect::<Vec<usize>>(),
-                (0..256).map(|_| rng.gen()).collect::<Vec<usize>>(),
-                (0..256).map(|_| rng.gen()).collect::<Vec<usize>>(),
-                (0..256).map(|_| rng.gen()).collect::<Vec<usize>>(),
-            ],
+            min_d: 0,
+            tab,
+            twist,
+            hasher,
         }
     }
 
-    fn hash(&self, x: &T) -> usize {
-        let h = x.hash_code();
-        (self.tab[0][h & 0xff]
-            ^ self.tab[1][(h >> 8) & 0xff]
-            ^ self.tab[2][(h >> 16) & 0xff]
-            ^ self.tab[3][(h >> 24) & 0xff])
-            & ((1 << self.d) - 1)
+    fn hash<Q: ?Sized>(&self, x: &Q) -> usize
+    where
+        S: BuildHashCode<Q>,
+    {
+        self.slot(self.hasher.hash_code(x))
     }
 
-    // 本では find() となっている
-    fn get(&self, x: &T) -> Option<&T> {
-        let mut i = self.hash(x);
+    // hash_code から表の位置を求める
+    fn slot(&self, code: usize) -> usize {
+        let mut c = (code as u64).to_le_bytes();
+        if let Some(twist) = &self.twist {
+            c[7] ^= (0..7).fold(0, |t, i| t ^ twist[i][c[i] as usize]);
+        }
+        let h = (0..8).fold(0, |h, i| h ^ self.tab[i][c[i] as usize]);
+        h & ((1 << self.d) - 1)
+    }
+
+    // hash_code が code の位置から辿り、eq を満たす値の位置を探す
+    // LinearHashMap からも使う
+    pub(crate) fn position<F: Fn(&T) -> bool>(&self, code: usize, eq: F) -> Option<usize> {
+        let mut i = self.slot(code);
         while self.t[i] != Item::Null {
             if let Item::Value(y) = &self.t[i] {
-                if y == x {
-                    return Some(y);
+                if eq(y) {
+                    return Some(i);
                 }
             }
             i = if i + 1 == self.t.len() { 0 } else { i + 1 };
@@ -61,11 +133,52 @@ impl<T: Hashable + Eq> LinearHashTable<T> {
         None
     }
 
+    pub(crate) fn value_at(&self, i: usize) -> &T {
+        match &self.t[i] {
+            Item::Value(x) => x,
+            _ => unreachable!(),
+        }
+    }
+
+    pub(crate) fn value_at_mut(&mut self, i: usize) -> &mut T {
+        match &mut self.t[i] {
+            Item::Value(x) => x,
+            _ => unreachable!(),
+        }
+    }
+
+    // 本では find() となっている
+    pub fn get<Q>(&self, x: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        let i = self.position(self.hasher.hash_code(x), |y| y.borrow() == x)?;
+        Some(self.value_at(i))
+    }
+
+    pub fn contains<Q>(&self, x: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        self.get(x).is_some()
+    }
+
     // 本では add() となっている
     pub fn insert(&mut self, x: T) -> bool {
         if self.get(&x).is_some() {
             return false;
         }
+        self.insert_new(x);
+        true
+    }
+
+    // x が含まれていないことがわかっているときの挿入
+    // 入れた位置を返す
+    pub(crate) fn insert_new(&mut self, x: T) -> usize {
         if 2 * (self.q + 1) > self.t.len() {
             self.resize();
         }
@@ -78,33 +191,98 @@ impl<T: Hashable + Eq> LinearHashTable<T> {
         }
         self.n += 1;
         self.t[i] = Item::Value(x);
-        true
+        i
     }
 
     // 説明では返り値は bool と言っているのにコードでは T を返している？
-    pub fn remove(&mut self, x: &T) -> bool {
-        let mut i = self.hash(x);
-        while self.t[i] != Item::Null {
-            if let Item::Value(y) = &self.t[i] {
-                if y == x {
-                    self.t[i] = Item::Del;
+    pub fn remove<Q>(&mut self, x: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        match self.position(self.hasher.hash_code(x), |y| y.borrow() == x) {
+            None => false,
+            Some(i) => {
+                self.remove_at(i);
+                true
+            }
+        }
+    }
+
+    // remove と同じだが、取り除いた値を返す
+    pub fn take<Q>(&mut self, x: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Eq + ?Sized,
+        S: BuildHashCode<Q>,
+    {
+        let i = self.position(self.hasher.hash_code(x), |y| y.borrow() == x)?;
+        Some(self.remove_at(i))
+    }
+
+    // x を入れ、等しい値が既にあればそれと置き換えて返す
+    pub fn replace(&mut self, x: T) -> Option<T> {
+        match self.position(self.hasher.hash_code(&x), |y| *y == x) {
+            Some(i) => Some(mem::replace(self.value_at_mut(i), x)),
+            None => {
+                self.insert_new(x);
+                None
+            }
+        }
+    }
+
+    // 位置 i の値を取り出す
+    // 表が縮むことがあるので、他の位置は無効になる
+    pub(crate) fn remove_at(&mut self, i: usize) -> T {
+        let item = mem::replace(&mut self.t[i], Item::Del);
+        self.n -= 1;
+        if self.is_sparse() {
+            self.resize();
+        }
+        match item {
+            Item::Value(x) => x,
+            _ => unreachable!(),
+        }
+    }
+
+    // f が false となる値を Del にし、減りすぎたら縮める
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        for item in self.t.iter_mut() {
+            if let Item::Value(x) = item {
+                if !f(x) {
+                    *item = Item::Del;
                     self.n -= 1;
-                    if 8 * self.n < self.t.len() {
-                        self.resize();
-                    }
-                    return true;
                 }
             }
-            i = if i + 1 == self.t.len() { 0 } else { i + 1 };
         }
-        false
+        if self.is_sparse() {
+            self.resize();
+        }
+    }
+
+    pub fn drain(&mut self) -> Drain<'_, T, S> {
+        Drain::new(self)
+    }
+
+    // 値が 1/8 未満になり、reserve した大きさより縮められるとき
+    fn is_sparse(&self) -> bool {
+        8 * self.n < self.t.len() && self.d > self.min_d
     }
 
+    // 値の個数に合った大きさ (3n 以上の最小の 2^d) に作り直す
+    // ただし reserve した大きさより小さくはしない
     fn resize(&mut self) {
-        let mut d = 1;
+        let mut d = self.min_d.max(1);
         while 1 << d < 3 * self.n {
             d += 1;
         }
+        self.resize_to(d);
+    }
+
+    // 大きさ 2^d の表に入れ直す
+    // Del はなくなるので q == n となる
+    fn resize_to(&mut self, d: usize) {
         let mut t_new = Vec::with_capacity(1 << d);
         t_new.resize_with(1 << d, || Item::Null);
         self.q = self.n;
@@ -121,9 +299,129 @@ impl<T: Hashable + Eq> LinearHashTable<T> {
         }
     }
 
+    // あと additional 個の値を resize なしで入れられるようにする
+    // n + additional 個が入る大きさは覚えておき、remove で値が減っても
+    // shrink_to_fit を呼ぶまではそれより縮めない
+    pub fn reserve(&mut self, additional: usize) {
+        let mut min_d = 0;
+        while 1 << min_d < 2 * (self.n + additional) {
+            min_d += 1;
+        }
+        self.min_d = self.min_d.max(min_d);
+        self.grow_for(additional);
+    }
+
+    // Del も insert 時の判定 2 * (q + 1) > t.len() に数えられるので、
+    // 足りなければ Del を掃除した上で 3 * (n + additional) 以上の大きさにする
+    // 下限は変えないので、読み込みのときの一時的な確保にも使う
+    pub(super) fn grow_for(&mut self, additional: usize) {
+        if 2 * (self.q + additional) <= self.t.len() {
+            return;
+        }
+        let mut d = self.d;
+        while 1 << d < 3 * (self.n + additional) {
+            d += 1;
+        }
+        self.resize_to(d);
+    }
+
+    // reserve した分も手放す
+    pub fn shrink_to_fit(&mut self) {
+        self.min_d = 0;
+        self.resize();
+    }
+
+    pub fn check(&self) -> Result<(), &str> {
+        if self.t.len() != 1 << self.d {
+            return Err("table size is not 2^d");
+        }
+        let n = self
+            .t
+            .iter()
+            .filter(|item| matches!(item, Item::Value(_)))
+            .count();
+        if n != self.n {
+            return Err("wrong n");
+        }
+        let q = self.t.iter().filter(|&item| *item != Item::Null).count();
+        if q != self.q {
+            return Err("wrong q");
+        }
+        if 2 * self.q > self.t.len() {
+            return Err("table is too full");
+        }
+        if self.d < self.min_d {
+            return Err("table is smaller than reserved");
+        }
+        // ハッシュ値の位置から Null を挟まずに辿り着けること
+        for (j, item) in self.t.iter().enumerate() {
+            if let Item::Value(x) = item {
+                let mut i = self.hash(x);
+                while i != j {
+                    if self.t[i] == Item::Null {
+                        return Err("value is unreachable");
+                    }
+                    i = if i + 1 == self.t.len() { 0 } else { i + 1 };
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, S> LinearHashTable<T, S> {
     pub fn len(&self) -> usize {
         self.n
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    // resize なしで入れられる値の個数
+    // Del も insert 時の判定に数えられるので、その分を引く
+    pub fn capacity(&self) -> usize {
+        self.t.len() / 2 - (self.q - self.n)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.t, self.n)
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    // new() 直後と同じ大きさ (reserve していればその大きさ) に戻す
+    pub fn clear(&mut self) {
+        self.t.clear();
+        self.t.resize_with(1 << self.min_d, || Item::Null);
+        self.n = 0;
+        self.q = 0;
+        self.d = self.min_d;
+    }
+}
+
+impl<T: Eq, S: BuildHashCode<T>> Extend<T> for LinearHashTable<T, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl<T: Eq, S: BuildHashCode<T> + Default> FromIterator<T> for LinearHashTable<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut table = Self::default();
+        table.extend(iter);
+        table
+    }
+}
+
+impl<T: Eq, S: BuildHashCode<T> + Default> Default for LinearHashTable<T, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for Item<T> {
@@ -136,7 +434,7 @@ impl<T: fmt::Debug> fmt::Debug for Item<T> {
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for LinearHashTable<T> {
+impl<T: fmt::Debug, S> fmt::Debug for LinearHashTable<T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[")?;
         let mut iter = self.t.iter();
@@ -150,17 +448,39 @@ impl<T: fmt::Debug> fmt::Debug for LinearHashTable<T> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{Hashable, LinearHashTable};
+// 値の列として読み書きする (順序は不定)
+#[cfg(feature = "serde")]
+impl<T: Serialize, H> Serialize for LinearHashTable<T, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
 
-    impl Hashable for i32 {
-        fn hash_code(&self) -> usize {
-            *self as usize
+#[cfg(feature = "serde")]
+impl<'de, T, S> Deserialize<'de> for LinearHashTable<T, S>
+where
+    T: Eq + Deserialize<'de>,
+    S: BuildHashCode<T> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Vec::deserialize(deserializer)?;
+        let mut table = Self::default();
+        table.grow_for(v.len());
+        for x in v {
+            table.insert(x);
         }
+        Ok(table)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LinearHashTable, Tabulation};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_linear_hash_table_hand() {
         let mut h = LinearHashTable::new();
         assert_eq!(h.get(&0), None);
@@ -209,4 +529,207 @@ mod tests {
 
         dbg!(h);
     }
+
+    #[test]
+    fn test_linear_hash_table_borrow() {
+        let mut h = LinearHashTable::new();
+        for s in &["foo", "bar", "baz"] {
+            assert!(h.insert(s.to_string()));
+        }
+        assert_eq!(h.get("bar").map(String::as_str), Some("bar"));
+        assert_eq!(h.get("qux"), None);
+        assert!(h.remove("foo"));
+        assert!(!h.remove("foo"));
+        assert_eq!(h.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_linear_hash_table_serde() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+        use serde_test::{assert_ser_tokens, Token};
+
+        let mut h = LinearHashTable::new();
+        h.insert(42);
+        assert_ser_tokens(
+            &h,
+            &[Token::Seq { len: Some(1) }, Token::I32(42), Token::SeqEnd],
+        );
+
+        let v = (0..1000).chain(0..10).collect::<Vec<i32>>();
+        let h =
+            LinearHashTable::<i32>::deserialize(SeqDeserializer::<_, Error>::new(v.into_iter()))
+                .unwrap();
+        h.check().unwrap();
+        assert_eq!(h.len(), 1000);
+        // 最初に確保した大きさのまま
+        assert_eq!(h.t.len(), 4096);
+        for i in 0..1000 {
+            assert_eq!(h.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn test_linear_hash_table_with_seed() {
+        let mut h1 = LinearHashTable::with_seed(42);
+        let mut h2 = LinearHashTable::with_rng(&mut StdRng::seed_from_u64(42));
+        for i in 0..100 {
+            h1.insert(i);
+            h2.insert(i);
+        }
+        // 同じシードなら同じ配置になる
+        assert_eq!(format!("{:?}", h1), format!("{:?}", h2));
+    }
+
+    // 上位 32 ビットだけが異なる値でも散らばること
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_linear_hash_table_high_bits() {
+        for &tabulation in &[Tabulation::Simple, Tabulation::Twisted] {
+            let mut rng = StdRng::seed_from_u64(23);
+            let mut h = LinearHashTable::with_rng_and_tabulation(&mut rng, tabulation);
+            for i in 0..1 << 12 {
+                h.insert((i as usize) << 32);
+            }
+            h.check().unwrap();
+            assert_eq!(h.d, 13);
+            // 各値について、ハッシュ値の位置から実際の位置までの距離
+            let m = h.t.len();
+            let mut buckets = vec![0; m];
+            let mut total = 0;
+            for (j, item) in h.t.iter().enumerate() {
+                if let super::Item::Value(x) = item {
+                    let i = h.hash(x);
+                    buckets[i] += 1;
+                    total += (j + m - i) % m;
+                }
+            }
+            // 占有率 1/2 の線形探索なら平均距離は 1/2 程度になる
+            assert!(total < 2 * h.len(), "{:?}: {}", tabulation, total);
+            assert!(*buckets.iter().max().unwrap() < 10, "{:?}", tabulation);
+        }
+    }
+
+    #[test]
+    fn test_linear_hash_table_iter() {
+        let mut h = (0..100).collect::<LinearHashTable<_>>();
+        assert_eq!(h.iter().len(), 100);
+        let mut seen = h.iter().copied().collect::<Vec<_>>();
+        seen.sort();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        assert_eq!((&h).into_iter().count(), 100);
+
+        h.retain(|&x| x % 3 == 0);
+        h.check().unwrap();
+        assert_eq!(h.len(), 34);
+        assert!(h.get(&3).is_some());
+        assert!(h.get(&4).is_none());
+        // 値が 1/8 未満になったので縮んでいる
+        h.retain(|&x| x < 6);
+        h.check().unwrap();
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.t.len(), 8);
+
+        h.extend(0..50);
+        h.check().unwrap();
+        assert_eq!(h.len(), 50);
+        // 途中で止めても残りは取り除かれる
+        assert_eq!(h.drain().take(10).count(), 10);
+        assert!(h.is_empty());
+        h.check().unwrap();
+        assert!(h.get(&0).is_none());
+
+        h.extend(0..50);
+        let mut drained = h.drain().collect::<Vec<_>>();
+        drained.sort();
+        assert_eq!(drained, (0..50).collect::<Vec<_>>());
+        assert!(h.is_empty());
+        h.check().unwrap();
+
+        h.extend(0..50);
+        h.clear();
+        assert!(h.is_empty());
+        h.check().unwrap();
+        h.insert(7);
+        let all = h.into_iter().collect::<Vec<_>>();
+        assert_eq!(all, vec![7]);
+    }
+
+    #[test]
+    fn test_linear_hash_table_lookup() {
+        let mut h = LinearHashTable::new();
+        h.insert("a".to_string());
+        assert!(h.contains("a"));
+        assert!(!h.contains("b"));
+        assert_eq!(h.replace("b".to_string()), None);
+        assert_eq!(h.replace("b".to_string()), Some("b".to_string()));
+        assert_eq!(h.take("a"), Some("a".to_string()));
+        assert_eq!(h.take("a"), None);
+        assert_eq!(h.len(), 1);
+        h.check().unwrap();
+    }
+
+    #[test]
+    fn test_linear_hash_table_capacity() {
+        let mut h = LinearHashTable::with_capacity(100);
+        assert!(h.capacity() >= 100);
+        let t_len = h.t.len();
+        for i in 0..100 {
+            h.insert(i);
+        }
+        // 宣言した個数までは resize されない
+        assert_eq!(h.t.len(), t_len);
+        h.check().unwrap();
+
+        h.reserve(1000);
+        assert!(h.capacity() >= 1100);
+        h.check().unwrap();
+        h.shrink_to_fit();
+        assert!(h.capacity() >= 100);
+        assert!(h.capacity() < 1100);
+        h.check().unwrap();
+
+        for i in 0..30 {
+            h.remove(&i);
+        }
+        // Del の分だけ capacity は減る
+        assert_eq!(h.capacity(), h.t.len() / 2 - 30);
+        // 足りなければ Del を掃除してから広げる
+        let additional = h.capacity() - h.len() + 1;
+        h.reserve(additional);
+        assert_eq!(h.q, h.n);
+        assert!(h.capacity() >= h.len() + additional);
+        h.check().unwrap();
+        for i in 0..100 {
+            assert_eq!(h.contains(&i), i >= 30);
+        }
+
+        // reserve した分は、値が減っても shrink_to_fit までは保たれる
+        let mut h = LinearHashTable::with_capacity(1000);
+        for i in 0..10 {
+            h.insert(i);
+        }
+        for i in 0..10 {
+            h.remove(&i);
+        }
+        assert!(h.capacity() >= 1000);
+        h.check().unwrap();
+        h.extend(0..10);
+        h.retain(|&x| x < 2);
+        assert!(h.capacity() >= 1000);
+        h.drain();
+        assert!(h.capacity() >= 1000);
+        h.clear();
+        assert!(h.capacity() >= 1000);
+        h.check().unwrap();
+        h.shrink_to_fit();
+        assert!(h.capacity() < 1000);
+        h.check().unwrap();
+
+        let mut h = LinearHashTable::<i32>::new();
+        assert_eq!(h.capacity(), 0);
+        h.reserve(0);
+        assert_eq!(h.t.len(), 1);
+    }
 }
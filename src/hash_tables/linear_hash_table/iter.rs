@@ -0,0 +1,146 @@
+use super::{BuildHashCode, ByHashable, Item, LinearHashTable};
+use std::iter::FusedIterator;
+use std::slice;
+use std::vec;
+
+// 順序は不定 (表の添字順)
+pub struct Iter<'a, T> {
+    iter: slice::Iter<'a, Item<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(super) fn new(t: &'a [Item<T>], len: usize) -> Self {
+        Iter {
+            iter: t.iter(),
+            len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in &mut self.iter {
+            if let Item::Value(x) = item {
+                self.len -= 1;
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            iter: self.iter.clone(),
+            len: self.len,
+        }
+    }
+}
+
+pub struct IntoIter<T> {
+    iter: vec::IntoIter<Item<T>>,
+    len: usize,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in &mut self.iter {
+            if let Item::Value(x) = item {
+                self.len -= 1;
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+// 取り出した場所は Del にしていき、最後に resize で表を縮める
+// 途中で止めても、残りは Drop のときに取り除く
+pub struct Drain<'a, T: Eq, S: BuildHashCode<T> = ByHashable> {
+    table: &'a mut LinearHashTable<T, S>,
+    i: usize,
+}
+
+impl<'a, T: Eq, S: BuildHashCode<T>> Drain<'a, T, S> {
+    pub(super) fn new(table: &'a mut LinearHashTable<T, S>) -> Self {
+        Drain { table, i: 0 }
+    }
+}
+
+impl<'a, T: Eq, S: BuildHashCode<T>> Iterator for Drain<'a, T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.i < self.table.t.len() {
+            let i = self.i;
+            self.i += 1;
+            if let Item::Value(_) = self.table.t[i] {
+                self.table.n -= 1;
+                if let Item::Value(x) = std::mem::replace(&mut self.table.t[i], Item::Del) {
+                    return Some(x);
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.table.n, Some(self.table.n))
+    }
+}
+
+impl<'a, T: Eq, S: BuildHashCode<T>> Drop for Drain<'a, T, S> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        if self.table.is_sparse() {
+            self.table.resize();
+        }
+    }
+}
+
+impl<'a, T: Eq, S: BuildHashCode<T>> ExactSizeIterator for Drain<'a, T, S> {}
+
+impl<'a, T: Eq, S: BuildHashCode<T>> FusedIterator for Drain<'a, T, S> {}
+
+impl<T, S> IntoIterator for LinearHashTable<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.t.into_iter(),
+            len: self.n,
+        }
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a LinearHashTable<T, S> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1 +1,3 @@
+pub mod hashable;
+pub mod linear_hash_map;
 pub mod linear_hash_table;
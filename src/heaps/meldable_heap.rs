@@ -1,41 +1,132 @@
+use crate::compare::{Compare, Key, Natural, Rev};
+use crate::dot::Dot;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering::{Greater, Less};
+use std::collections::VecDeque;
 use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
 
-struct Node<T: Ord> {
+pub mod addressable;
+mod iter;
+
+pub use addressable::{AddressableMeldableHeap, Handle};
+pub use iter::{Drain, IntoIter, Iter};
+
+struct Node<T> {
     value: T,
     left: Option<Box<Node<T>>>,
     right: Option<Box<Node<T>>>,
 }
 
-pub struct MeldableHeap<T: Ord> {
+// 乱数生成器と順序を差し替えられる
+// new() は thread_rng() から種を取った StdRng と Ord による順序 (最小値から取り出す) を使う
+// ThreadRng は Send でないので、既定では持たせない
+pub struct MeldableHeap<T, R: Rng = StdRng, C: Compare<T> = Natural> {
     root: Option<Box<Node<T>>>,
     len: usize,
+    rng: R,
+    cmp: C,
+}
+
+// 最大値から取り出す
+pub type MaxMeldableHeap<T, R = StdRng> = MeldableHeap<T, R, Rev<Natural>>;
+
+// 既定の乱数生成器
+fn default_rng() -> StdRng {
+    StdRng::from_rng(rand::thread_rng()).unwrap()
 }
 
 impl<T: Ord> MeldableHeap<T> {
     pub fn new() -> Self {
-        MeldableHeap { root: None, len: 0 }
-    }
-
-    fn merge(mut h1: Option<Box<Node<T>>>, mut h2: Option<Box<Node<T>>>) -> Option<Box<Node<T>>> {
-        match (h1.as_mut(), h2.as_mut()) {
-            (None, _) => h2,
-            (_, None) => h1,
-            (Some(b1), Some(b2)) if b1.value > b2.value => Self::merge(h2, h1),
-            (Some(b1), _) => {
-                if rand::random() {
-                    b1.left = Self::merge(b1.left.take(), h2);
-                } else {
-                    b1.right = Self::merge(b1.right.take(), h2);
+        Self::with_rng(default_rng())
+    }
+
+    // 再現性が必要なとき
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<T: Ord, R: Rng> MeldableHeap<T, R> {
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_rng_and_comparator(rng, Natural)
+    }
+}
+
+impl<T, C: Compare<T>> MeldableHeap<T, StdRng, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_rng_and_comparator(default_rng(), cmp)
+    }
+}
+
+// f(x) の小さいものから取り出す
+impl<T, K: Ord, F: Fn(&T) -> K> MeldableHeap<T, StdRng, Key<F, K>> {
+    pub fn with_key(f: F) -> Self {
+        Self::with_comparator(Key::new(f))
+    }
+}
+
+impl<T, R: Rng, C: Compare<T>> MeldableHeap<T, R, C> {
+    pub fn with_rng_and_comparator(rng: R, cmp: C) -> Self {
+        MeldableHeap {
+            root: None,
+            len: 0,
+            rng,
+            cmp,
+        }
+    }
+
+    // 根の小さい方を残し、その左右どちらかの子ともう一方を merge する、を繰り返す
+    // 再帰の代わりに、結果を書き込む場所を辿っていく
+    fn merge(
+        mut h1: Option<Box<Node<T>>>,
+        mut h2: Option<Box<Node<T>>>,
+        rng: &mut R,
+        cmp: &C,
+    ) -> Option<Box<Node<T>>> {
+        let mut root = None;
+        let mut slot = &mut root;
+        loop {
+            match (h1, h2) {
+                (None, h) | (h, None) => {
+                    *slot = h;
+                    return root;
+                }
+                (Some(mut b1), Some(mut b2)) => {
+                    if cmp.compare(&b1.value, &b2.value) == Greater {
+                        mem::swap(&mut b1, &mut b2);
+                    }
+                    let left = rng.gen();
+                    h1 = if left {
+                        b1.left.take()
+                    } else {
+                        b1.right.take()
+                    };
+                    h2 = Some(b2);
+                    let node = slot.insert(b1);
+                    slot = if left {
+                        &mut node.left
+                    } else {
+                        &mut node.right
+                    };
                 }
-                h1
             }
         }
     }
 
     // より Rust らしい実装
     // 本では absorb() となっている
-    pub fn append(&mut self, other: &mut MeldableHeap<T>) {
-        self.root = Self::merge(self.root.take(), other.root.take());
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = Self::merge(
+            self.root.take(),
+            other.root.take(),
+            &mut self.rng,
+            &self.cmp,
+        );
         self.len += other.len;
         other.len = 0;
     }
@@ -47,7 +138,7 @@ impl<T: Ord> MeldableHeap<T> {
             left: None,
             right: None,
         }));
-        self.root = Self::merge(node, self.root.take());
+        self.root = Self::merge(node, self.root.take(), &mut self.rng, &self.cmp);
         self.len += 1;
     }
 
@@ -56,13 +147,30 @@ impl<T: Ord> MeldableHeap<T> {
         match self.root.take() {
             None => None,
             Some(b) => {
-                self.root = Self::merge(b.left, b.right);
+                self.root = Self::merge(b.left, b.right, &mut self.rng, &self.cmp);
                 self.len -= 1;
                 Some(b.value)
             }
         }
     }
 
+    pub fn peek(&self) -> Option<&T> {
+        self.root.as_ref().map(|b| &b.value)
+    }
+
+    // 最小値を書き換えられるようにする
+    // 書き換えた場合は、PeekMut を捨てるときに根を付け直す
+    pub fn peek_mut(&mut self) -> Option<PeekMut<'_, T, R, C>> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(PeekMut {
+                heap: self,
+                modified: false,
+            })
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
@@ -70,9 +178,163 @@ impl<T: Ord> MeldableHeap<T> {
     pub fn is_empty(&self) -> bool {
         self.root.is_none()
     }
+
+    pub fn clear(&mut self) {
+        self.drain();
+    }
+
+    // 順序は不定
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root, self.len)
+    }
+
+    // 順序は不定
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        let len = mem::replace(&mut self.len, 0);
+        Drain::new(IntoIter::new(self.root.take(), len))
+    }
+
+    // BinaryHeap::into_sorted_vec と同じく、比較器によらず Ord の昇順
+    // 取り出す順に欲しい場合は pop を繰り返す
+    pub fn into_sorted_vec(mut self) -> Vec<T>
+    where
+        T: Ord,
+    {
+        let mut v = Vec::with_capacity(self.len);
+        while let Some(value) = self.pop() {
+            v.push(value);
+        }
+        v.sort();
+        v
+    }
+
+    // 順序は不定
+    pub fn into_vec(self) -> Vec<T> {
+        self.into_iter().collect()
+    }
+
+    // f が false となる値を取り除き、残りから作り直す
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let values = self.drain().filter(|value| f(value)).collect::<Vec<_>>();
+        self.rebuild(values);
+    }
+
+    // 1 要素のヒープを 2 つずつ merge していく
+    // 大きさの近いものどうしを merge するので全体で O(n)
+    fn rebuild(&mut self, values: Vec<T>) {
+        self.len = values.len();
+        let mut queue = values
+            .into_iter()
+            .map(|value| {
+                Some(Box::new(Node {
+                    value,
+                    left: None,
+                    right: None,
+                }))
+            })
+            .collect::<VecDeque<_>>();
+        while queue.len() > 1 {
+            let h1 = queue.pop_front().unwrap();
+            let h2 = queue.pop_front().unwrap();
+            queue.push_back(Self::merge(h1, h2, &mut self.rng, &self.cmp));
+        }
+        self.root = queue.pop_front().flatten();
+    }
+
+    pub fn check(&self) -> Result<(), &str> {
+        let mut count = 0;
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            if let Some(b) = node {
+                count += 1;
+                for child in [&b.left, &b.right].iter() {
+                    if let Some(c) = child {
+                        if self.cmp.compare(&c.value, &b.value) == Less {
+                            return Err("heap order violated");
+                        }
+                    }
+                    stack.push(child);
+                }
+            }
+        }
+        if count != self.len {
+            return Err("wrong len");
+        }
+        Ok(())
+    }
 }
 
-impl<T: Ord + fmt::Debug> fmt::Debug for MeldableHeap<T> {
+// 既定の Drop は子を再帰的に解放するので、節を一つずつ外して解放する
+impl<T, R: Rng, C: Compare<T>> Drop for MeldableHeap<T, R, C> {
+    fn drop(&mut self) {
+        self.drain();
+    }
+}
+
+// std::collections::binary_heap::PeekMut に倣ったもの
+pub struct PeekMut<'a, T, R: Rng = StdRng, C: Compare<T> = Natural> {
+    heap: &'a mut MeldableHeap<T, R, C>,
+    modified: bool,
+}
+
+impl<'a, T, R: Rng, C: Compare<T>> PeekMut<'a, T, R, C> {
+    pub fn pop(this: Self) -> T {
+        let mut this = mem::ManuallyDrop::new(this);
+        this.heap.pop().unwrap()
+    }
+}
+
+impl<'a, T, R: Rng, C: Compare<T>> Deref for PeekMut<'a, T, R, C> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.root.as_ref().unwrap().value
+    }
+}
+
+impl<'a, T, R: Rng, C: Compare<T>> DerefMut for PeekMut<'a, T, R, C> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        &mut self.heap.root.as_mut().unwrap().value
+    }
+}
+
+// 値が増えたかもしれないので、根を外して子どうしを merge し、根を merge し直す
+impl<'a, T, R: Rng, C: Compare<T>> Drop for PeekMut<'a, T, R, C> {
+    fn drop(&mut self) {
+        if self.modified {
+            let mut root = self.heap.root.take().unwrap();
+            let (rng, cmp) = (&mut self.heap.rng, &self.heap.cmp);
+            let rest = MeldableHeap::merge(root.left.take(), root.right.take(), rng, cmp);
+            self.heap.root = MeldableHeap::merge(Some(root), rest, rng, cmp);
+        }
+    }
+}
+
+impl<T, C: Compare<T> + Default> Default for MeldableHeap<T, StdRng, C> {
+    fn default() -> Self {
+        Self::with_rng_and_comparator(default_rng(), C::default())
+    }
+}
+
+impl<T: fmt::Debug, R: Rng, C: Compare<T>> MeldableHeap<T, R, C> {
+    // Graphviz の DOT 形式
+    pub fn to_dot(&self) -> String {
+        let mut dot = Dot::new("MeldableHeap", false);
+        let mut stack = vec![(None, "", &self.root)];
+        while let Some((parent, label, node)) = stack.pop() {
+            if let Some(b) = node {
+                let id = Some(dot.node(&b.value, None));
+                dot.edge(parent, id, label);
+                stack.push((id, "R", &b.right));
+                stack.push((id, "L", &b.left));
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T: fmt::Debug, R: Rng, C: Compare<T>> fmt::Debug for MeldableHeap<T, R, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "MeldableHeap {{")?;
         let mut stack = vec![];
@@ -92,9 +354,34 @@ impl<T: Ord + fmt::Debug> fmt::Debug for MeldableHeap<T> {
     }
 }
 
+// 値の列として読み書きする (順序は不定)
+#[cfg(feature = "serde")]
+impl<T: Serialize, R: Rng, C: Compare<T>> Serialize for MeldableHeap<T, R, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, C> Deserialize<'de> for MeldableHeap<T, StdRng, C>
+where
+    T: Deserialize<'de>,
+    C: Compare<T> + Default,
+{
+    // 一つずつ insert せず、まとめて O(n) で作る
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut heap = Self::default();
+        heap.rebuild(Vec::deserialize(deserializer)?);
+        Ok(heap)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::MeldableHeap;
+    use super::{AddressableMeldableHeap, MaxMeldableHeap, MeldableHeap, PeekMut};
+    use crate::compare::{Natural, Rev};
+    use rand::prelude::*;
+    use rand::rngs::mock::StepRng;
 
     #[test]
     fn test_meldable_heap() {
@@ -116,4 +403,208 @@ mod tests {
         assert_eq!(h1.pop(), Some(2));
         dbg!(&h1);
     }
+
+    #[test]
+    fn test_meldable_heap_peek_mut() {
+        let mut h = MeldableHeap::new();
+        assert!(h.peek().is_none());
+        assert!(h.peek_mut().is_none());
+        for x in &[5, 1, 8, 3, 9] {
+            h.insert(*x);
+        }
+        assert_eq!(h.peek(), Some(&1));
+        // 読むだけなら形は変わらない
+        assert_eq!(*h.peek_mut().unwrap(), 1);
+        *h.peek_mut().unwrap() = 6;
+        h.check().unwrap();
+        assert_eq!(h.peek(), Some(&3));
+        *h.peek_mut().unwrap() -= 3;
+        assert_eq!(h.peek(), Some(&0));
+        assert_eq!(PeekMut::pop(h.peek_mut().unwrap()), 0);
+        assert_eq!(h.len(), 4);
+        h.check().unwrap();
+        assert_eq!(h.into_sorted_vec(), vec![5, 6, 8, 9]);
+    }
+
+    #[test]
+    fn test_meldable_heap_iter() {
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        let mut h = MeldableHeap::new();
+        for &x in &v {
+            h.insert(x);
+        }
+        assert_eq!(h.iter().len(), 100);
+        let mut seen = h.iter().copied().collect::<Vec<_>>();
+        seen.sort();
+        assert_eq!(seen, (0..100).collect::<Vec<_>>());
+        assert_eq!((&h).into_iter().count(), 100);
+
+        h.retain(|&x| x % 3 == 0);
+        h.check().unwrap();
+        assert_eq!(h.len(), 34);
+        let mut drained = h.drain().collect::<Vec<_>>();
+        assert!(h.is_empty());
+        assert_eq!(h.len(), 0);
+        drained.sort();
+        assert_eq!(drained, (0..100).filter(|x| x % 3 == 0).collect::<Vec<_>>());
+
+        for &x in &v {
+            h.insert(x);
+        }
+        let mut all = h.into_vec();
+        all.sort();
+        assert_eq!(all, (0..100).collect::<Vec<_>>());
+    }
+
+    // 減少列を入れると根から一本道の木になるので、再帰的な Drop ではスタックが溢れる
+    // (各 merge はすぐ終わるので、merge の深さは test_meldable_heap_deep_merge で見る)
+    fn stress(n: i64) {
+        let mut rng = StdRng::seed_from_u64(18);
+        let mut h = MeldableHeap::with_seed(18);
+        for i in 0..n {
+            let x = if rng.gen_range(0, 100) == 0 {
+                rng.gen_range(0, n)
+            } else {
+                n - i
+            };
+            h.insert(x);
+        }
+        assert_eq!(h.len(), n as usize);
+        let mut other = MeldableHeap::with_seed(19);
+        for i in 0..1000 {
+            other.insert(-i);
+        }
+        h.append(&mut other);
+        let mut last = i64::MIN;
+        for _ in 0..2000 {
+            let x = h.pop().unwrap();
+            assert!(last <= x);
+            last = x;
+        }
+        assert_eq!(h.len(), n as usize - 1000);
+    }
+
+    #[test]
+    fn test_meldable_heap_stress() {
+        stress(1_000_000);
+    }
+
+    // 時間がかかるので cargo test -- --ignored で実行する
+    #[test]
+    #[ignore]
+    fn test_meldable_heap_stress_large() {
+        stress(10_000_000);
+    }
+
+    // 常に右を選ぶ乱数で減少列を入れると、右の子だけの一本道になる
+    // 偶数と奇数の一本道を merge すると両方を交互に辿るので、深さが 2N になる
+    #[test]
+    fn test_meldable_heap_deep_merge() {
+        const N: i64 = 1_000_000;
+        let mut h1 = MeldableHeap::with_rng(StepRng::new(0, 0));
+        let mut h2 = MeldableHeap::with_rng(StepRng::new(0, 0));
+        for i in (0..N).rev() {
+            h1.insert(2 * i);
+            h2.insert(2 * i + 1);
+        }
+        h1.append(&mut h2);
+        assert_eq!(h1.len(), 2 * N as usize);
+        for i in 0..2 * N {
+            assert_eq!(h1.pop(), Some(i));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_meldable_heap_serde() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+        use serde_test::{assert_ser_tokens, Token};
+
+        let mut h = MeldableHeap::new();
+        h.insert(42);
+        assert_ser_tokens(
+            &h,
+            &[Token::Seq { len: Some(1) }, Token::I32(42), Token::SeqEnd],
+        );
+
+        let v = vec![5, 3, 5, 1, 3];
+        let mut h =
+            MeldableHeap::<i32>::deserialize(SeqDeserializer::<_, Error>::new(v.into_iter()))
+                .unwrap();
+        h.check().unwrap();
+        let mut popped = vec![];
+        while let Some(x) = h.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped, vec![1, 3, 3, 5, 5]);
+    }
+
+    #[test]
+    fn test_meldable_heap_with_seed() {
+        let mut h1 = MeldableHeap::with_seed(42);
+        let mut h2 = MeldableHeap::with_rng(StdRng::seed_from_u64(42));
+        for i in 0..100 {
+            h1.insert(i * 7 % 100);
+            h2.insert(i * 7 % 100);
+        }
+        h1.pop();
+        h2.pop();
+        // 同じシードなら同じ形になる
+        assert_eq!(h1.to_dot(), h2.to_dot());
+        assert_eq!(h1.into_sorted_vec(), (1..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_meldable_heap_comparator() {
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+
+        let mut max: MaxMeldableHeap<i32> = MaxMeldableHeap::default();
+        for &x in &v {
+            max.insert(x);
+        }
+        max.check().unwrap();
+        assert_eq!(max.peek(), Some(&99));
+        *max.peek_mut().unwrap() -= 50;
+        assert_eq!(max.pop(), Some(98));
+        let mut expected = (0..98).chain(Some(49)).collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(max.into_sorted_vec(), expected);
+
+        // 1 の位で比べ、同じなら 10 の位で比べる
+        let mut key = MeldableHeap::with_key(|&x: &i32| (x % 10, x / 10));
+        let mut closure = MeldableHeap::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+        for &x in &v {
+            key.insert(x);
+            closure.insert(x);
+        }
+        key.check().unwrap();
+        closure.check().unwrap();
+        let mut expected = v.clone();
+        expected.sort_by_key(|&x| (x % 10, x / 10));
+        assert_eq!(
+            std::iter::from_fn(|| key.pop()).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(closure.pop(), Some(99));
+
+        let mut seeded =
+            MeldableHeap::with_rng_and_comparator(StdRng::seed_from_u64(20), Rev(Natural));
+        seeded.insert(1);
+        seeded.insert(2);
+        assert_eq!(seeded.pop(), Some(2));
+    }
+
+    // 既定の型は別のスレッドに渡せる
+    #[test]
+    fn test_meldable_heap_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<MeldableHeap<i32>>();
+        assert_send::<MaxMeldableHeap<i32>>();
+        assert_send::<AddressableMeldableHeap<i32>>();
+    }
 }
@@ -0,0 +1,419 @@
+// insert で返すハンドルを使って、値の参照・減少・削除ができるもの
+// 節は Vec に置いて添字で指し、親への添字も持たせる
+// 部分木の切り出しと merge による再結合で decrease_key と remove を行う
+use super::default_rng;
+use crate::compare::{Compare, Natural};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering::{Greater, Less};
+use std::fmt;
+
+struct Node<T> {
+    value: T,
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// 空いた場所は再利用するので、世代を比べて古いハンドルを見分ける
+struct Slot<T> {
+    generation: usize,
+    node: Option<Node<T>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Handle {
+    index: usize,
+    generation: usize,
+}
+
+// MeldableHeap と同じく、乱数生成器と順序を差し替えられる
+pub struct AddressableMeldableHeap<T, R: Rng = StdRng, C: Compare<T> = Natural> {
+    slots: Vec<Slot<T>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    len: usize,
+    rng: R,
+    cmp: C,
+}
+
+impl<T: Ord> AddressableMeldableHeap<T> {
+    pub fn new() -> Self {
+        Self::with_rng(default_rng())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<T: Ord, R: Rng> AddressableMeldableHeap<T, R> {
+    pub fn with_rng(rng: R) -> Self {
+        Self::with_rng_and_comparator(rng, Natural)
+    }
+}
+
+impl<T, C: Compare<T>> AddressableMeldableHeap<T, StdRng, C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_rng_and_comparator(default_rng(), cmp)
+    }
+}
+
+impl<T, R: Rng, C: Compare<T>> AddressableMeldableHeap<T, R, C> {
+    pub fn with_rng_and_comparator(rng: R, cmp: C) -> Self {
+        AddressableMeldableHeap {
+            slots: vec![],
+            free: vec![],
+            root: None,
+            len: 0,
+            rng,
+            cmp,
+        }
+    }
+
+    fn node(&self, i: usize) -> &Node<T> {
+        self.slots[i].node.as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, i: usize) -> &mut Node<T> {
+        self.slots[i].node.as_mut().unwrap()
+    }
+
+    // 有効なハンドルなら節の添字
+    fn index(&self, handle: Handle) -> Option<usize> {
+        let slot = self.slots.get(handle.index)?;
+        if slot.generation == handle.generation && slot.node.is_some() {
+            Some(handle.index)
+        } else {
+            None
+        }
+    }
+
+    fn set_parent(&mut self, child: Option<usize>, parent: Option<usize>) {
+        if let Some(c) = child {
+            self.node_mut(c).parent = parent;
+        }
+    }
+
+    // p の左または右の子を child にする
+    fn link(&mut self, p: usize, left: bool, child: Option<usize>) {
+        let parent = self.node_mut(p);
+        if left {
+            parent.left = child;
+        } else {
+            parent.right = child;
+        }
+        self.set_parent(child, Some(p));
+    }
+
+    // 返り値の根の parent は呼び出し側で設定する
+    // MeldableHeap::merge と同じく、結果を書き込む場所 (親と左右) を辿る
+    fn merge(&mut self, mut h1: Option<usize>, mut h2: Option<usize>) -> Option<usize> {
+        let mut root = None;
+        let mut slot = None;
+        loop {
+            let (a, b) = match (h1, h2) {
+                (Some(a), Some(b))
+                    if self.cmp.compare(&self.node(a).value, &self.node(b).value) == Greater =>
+                {
+                    (b, a)
+                }
+                (Some(a), Some(b)) => (a, b),
+                (None, h) | (h, None) => match slot {
+                    None => return h,
+                    Some((p, left)) => {
+                        self.link(p, left, h);
+                        return root;
+                    }
+                },
+            };
+            match slot {
+                None => root = Some(a),
+                Some((p, left)) => self.link(p, left, Some(a)),
+            }
+            let left = self.rng.gen();
+            let node = self.node_mut(a);
+            h1 = if left {
+                node.left.take()
+            } else {
+                node.right.take()
+            };
+            h2 = Some(b);
+            slot = Some((a, left));
+        }
+    }
+
+    // i を根とする部分木を親から切り離す
+    // 親と、左右どちらの子だったかを返す
+    fn cut(&mut self, i: usize) -> Option<(usize, bool)> {
+        match self.node_mut(i).parent.take() {
+            None => {
+                self.root = None;
+                None
+            }
+            Some(p) => {
+                let parent = self.node_mut(p);
+                let left = parent.left == Some(i);
+                if left {
+                    parent.left = None;
+                } else {
+                    parent.right = None;
+                }
+                Some((p, left))
+            }
+        }
+    }
+
+    // i を根とする部分木を全体に merge し直す
+    fn meld_root(&mut self, i: usize) {
+        let root = self.root.take();
+        self.root = self.merge(root, Some(i));
+        self.set_parent(self.root, None);
+    }
+
+    pub fn insert(&mut self, value: T) -> Handle {
+        let node = Node {
+            value,
+            parent: None,
+            left: None,
+            right: None,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index].node = Some(node);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.meld_root(index);
+        self.len += 1;
+        Handle {
+            index,
+            generation: self.slots[index].generation,
+        }
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.index(handle).map(|i| &self.node(i).value)
+    }
+
+    // 値を value に減らし、元の値を Ok で返す
+    // 無効なハンドルか値が増える場合は、何もせずに value を Err で返す
+    pub fn decrease_key(&mut self, handle: Handle, value: T) -> Result<T, T> {
+        let i = match self.index(handle) {
+            Some(i) => i,
+            None => return Err(value),
+        };
+        if self.cmp.compare(&value, &self.node(i).value) == Greater {
+            return Err(value);
+        }
+        let old = std::mem::replace(&mut self.node_mut(i).value, value);
+        // 部分木の中の順序は保たれているので、親から外して merge し直せばよい
+        if self.node(i).parent.is_some() {
+            self.cut(i);
+            self.meld_root(i);
+        }
+        Ok(old)
+    }
+
+    // 無効なハンドルなら None
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let i = self.index(handle)?;
+        let side = self.cut(i);
+        let node = self.slots[i].node.take().unwrap();
+        self.slots[i].generation += 1;
+        self.free.push(i);
+        self.set_parent(node.left, None);
+        self.set_parent(node.right, None);
+        let merged = self.merge(node.left, node.right);
+        // 切り離した側に戻す
+        match side {
+            None => {
+                self.root = merged;
+                self.set_parent(merged, None);
+            }
+            Some((p, left)) => self.link(p, left, merged),
+        }
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.root.map(|i| &self.node(i).value)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let root = self.root?;
+        let generation = self.slots[root].generation;
+        self.remove(Handle {
+            index: root,
+            generation,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn check(&self) -> Result<(), &str> {
+        let mut count = 0;
+        let mut stack = vec![(None, self.root)];
+        while let Some((parent, node)) = stack.pop() {
+            if let Some(i) = node {
+                let node = match &self.slots[i].node {
+                    None => return Err("reachable slot is vacant"),
+                    Some(node) => node,
+                };
+                if node.parent != parent {
+                    return Err("wrong parent");
+                }
+                if let Some(p) = parent {
+                    if self.cmp.compare(&node.value, &self.node(p).value) == Less {
+                        return Err("heap order violated");
+                    }
+                }
+                count += 1;
+                stack.push((Some(i), node.left));
+                stack.push((Some(i), node.right));
+            }
+        }
+        if count != self.len {
+            return Err("wrong len");
+        }
+        if self.slots.len() - self.free.len() != self.len {
+            return Err("unreachable node");
+        }
+        Ok(())
+    }
+}
+
+impl<T, C: Compare<T> + Default> Default for AddressableMeldableHeap<T, StdRng, C> {
+    fn default() -> Self {
+        Self::with_rng_and_comparator(default_rng(), C::default())
+    }
+}
+
+impl<T: fmt::Debug, R: Rng, C: Compare<T>> fmt::Debug for AddressableMeldableHeap<T, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "AddressableMeldableHeap {{")?;
+        let mut stack = vec![(0, self.root)];
+        while let Some((d, node)) = stack.pop() {
+            if let Some(i) = node {
+                let node = self.node(i);
+                write!(f, "{}", "    ".repeat(1 + d))?;
+                writeln!(f, "{:?}", node.value)?;
+                stack.push((d + 1, node.right));
+                stack.push((d + 1, node.left));
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AddressableMeldableHeap;
+    use crate::compare::{Natural, Rev};
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_addressable_meldable_heap() {
+        let mut heap = AddressableMeldableHeap::new();
+        let a = heap.insert(5);
+        let b = heap.insert(3);
+        let c = heap.insert(8);
+        assert_eq!(heap.peek(), Some(&3));
+        assert_eq!(heap.decrease_key(c, 1), Ok(8));
+        assert_eq!(heap.get(c), Some(&1));
+        assert_eq!(heap.decrease_key(a, 6), Err(6));
+        assert_eq!(heap.get(a), Some(&5));
+        assert_eq!(heap.peek(), Some(&1));
+        assert_eq!(heap.remove(b), Some(3));
+        assert_eq!(heap.remove(b), None);
+        assert_eq!(heap.get(b), None);
+        assert_eq!(heap.decrease_key(b, 0), Err(0));
+        heap.check().unwrap();
+        // 空いた場所が再利用されても古いハンドルは無効のまま
+        let d = heap.insert(4);
+        assert_eq!(heap.get(b), None);
+        assert_eq!(heap.get(d), Some(&4));
+        assert_eq!(heap.pop(), Some(1));
+        assert_eq!(heap.get(c), None);
+        assert_eq!(heap.pop(), Some(4));
+        assert_eq!(heap.get(a), Some(&5));
+        assert_eq!(heap.len(), 1);
+    }
+
+    #[test]
+    fn test_addressable_meldable_heap_random() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let mut heap = AddressableMeldableHeap::with_seed(16);
+        // (値, 挿入番号) を値として持ち、同じ組を BTreeSet でも管理する
+        let mut set = BTreeSet::new();
+        let mut handles = vec![];
+        for id in 0..2000 {
+            match rng.gen_range(0, 4) {
+                0 => {
+                    let x = (rng.gen_range(0, 1000), id);
+                    handles.push((heap.insert(x), x));
+                    set.insert(x);
+                }
+                1 if !handles.is_empty() => {
+                    let k = rng.gen_range(0, handles.len());
+                    let (h, x) = handles[k];
+                    let y = (x.0 - rng.gen_range(0, 100), x.1);
+                    assert_eq!(heap.decrease_key(h, y), Ok(x));
+                    handles[k].1 = y;
+                    set.remove(&x);
+                    set.insert(y);
+                }
+                2 if !handles.is_empty() => {
+                    let (h, x) = handles.swap_remove(rng.gen_range(0, handles.len()));
+                    assert_eq!(heap.remove(h), Some(x));
+                    set.remove(&x);
+                }
+                _ => {
+                    let x = heap.pop();
+                    assert_eq!(x, set.iter().next().copied());
+                    if let Some(x) = x {
+                        set.remove(&x);
+                        handles.retain(|&(_, y)| y != x);
+                    }
+                }
+            }
+            heap.check().unwrap();
+            assert_eq!(heap.len(), set.len());
+            for &(h, x) in &handles {
+                assert_eq!(heap.get(h), Some(&x));
+            }
+        }
+    }
+
+    #[test]
+    fn test_addressable_meldable_heap_comparator() {
+        let mut heap = AddressableMeldableHeap::with_comparator(Rev(Natural));
+        let a = heap.insert(5);
+        heap.insert(3);
+        heap.insert(8);
+        assert_eq!(heap.peek(), Some(&8));
+        // 最大値から取り出すので、値を増やすのが decrease_key になる
+        assert_eq!(heap.decrease_key(a, 10), Ok(5));
+        assert_eq!(heap.peek(), Some(&10));
+        heap.check().unwrap();
+        assert_eq!(heap.pop(), Some(10));
+        assert_eq!(heap.pop(), Some(8));
+        assert_eq!(heap.pop(), Some(3));
+        assert_eq!(heap.pop(), None);
+    }
+}
@@ -0,0 +1,132 @@
+use super::{MeldableHeap, Node};
+use crate::compare::Compare;
+use rand::Rng;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+
+// 順序は不定 (行きがけ順)
+pub struct Iter<'a, T> {
+    stack: Vec<&'a Node<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    pub(super) fn new(root: &'a Option<Box<Node<T>>>, len: usize) -> Self {
+        Iter {
+            stack: root.as_deref().into_iter().collect(),
+            len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        self.len -= 1;
+        Some(&node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+// 所有権を持って辿る
+// 取り出した節の子をスタックに積むので、木を壊しながら進む
+pub struct IntoIter<T> {
+    stack: Vec<Box<Node<T>>>,
+    len: usize,
+}
+
+impl<T> IntoIter<T> {
+    pub(super) fn new(root: Option<Box<Node<T>>>, len: usize) -> Self {
+        IntoIter {
+            stack: root.into_iter().collect(),
+            len,
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = *self.stack.pop()?;
+        self.stack.extend(node.right);
+        self.stack.extend(node.left);
+        self.len -= 1;
+        Some(node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+// 残りの節も一つずつ外して解放する
+impl<T> Drop for IntoIter<T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+// drain() の時点でヒープは空になり、値は Drain が持つ
+pub struct Drain<'a, T> {
+    iter: IntoIter<T>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> Drain<'a, T> {
+    pub(super) fn new(iter: IntoIter<T>) -> Self {
+        Drain {
+            iter,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Drain<'a, T> {}
+
+impl<'a, T> FusedIterator for Drain<'a, T> {}
+
+impl<T, R: Rng, C: Compare<T>> IntoIterator for MeldableHeap<T, R, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter::new(self.root.take(), self.len)
+    }
+}
+
+impl<'a, T, R: Rng, C: Compare<T>> IntoIterator for &'a MeldableHeap<T, R, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1 +1,3 @@
 pub mod meldable_heap;
+
+pub use crate::compare::{Compare, Key, Natural, Rev};
@@ -1,5 +1,10 @@
+pub mod compare;
+mod dot;
+#[cfg(test)]
+mod fuzz;
 pub mod hash_tables;
 pub mod heaps;
 pub mod red_black_tree;
 pub mod red_black_tree_v1;
 pub mod sorting_algorithms;
+pub mod sset;
@@ -1,6 +1,23 @@
-use std::cmp::Ordering::{Equal, Greater, Less};
+use crate::dot::Dot;
+use std::borrow::Borrow;
+use std::cmp::Ordering::{self, Equal, Greater, Less};
 use std::fmt;
 use std::mem;
+use std::ops::RangeBounds;
+
+mod augment;
+mod build;
+mod iter;
+mod join;
+pub mod map;
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+pub use crate::compare::{Compare, Key, Natural, Rev};
+pub use augment::{Augment, Sum};
+pub(crate) use build::build_balanced;
+pub use iter::{IntoIter, Iter, Range};
+pub use map::RedBlackTreeMap;
 
 enum Color {
     Red,
@@ -11,17 +28,32 @@ use Color::{Black, Red};
 
 // 外部の型に実装を加えることはできないので、扱いやすくするためのラッパー
 // null pointer optimization の恩恵を受けるため Option<Box< >> を使用
-struct Node<T>(Option<Box<NodeInner<T>>>);
+struct Node<T, A: Augment<T> = ()>(Option<Box<NodeInner<T, A>>>);
 
 // 内部構造
-struct NodeInner<T> {
+struct NodeInner<T, A: Augment<T>> {
     color: Color,
     value: T,
-    left: Node<T>,
-    right: Node<T>,
+    size: usize,         // 部分木の節の個数
+    summary: A::Summary, // 部分木の値の集約
+    left: Node<T, A>,
+    right: Node<T, A>,
 }
 
-impl<T: Ord> Node<T> {
+impl<T, A: Augment<T>> Node<T, A> {
+    fn new(color: Color, value: T, left: Self, right: Self) -> Self {
+        let mut node = Node(Some(Box::new(NodeInner {
+            color,
+            summary: A::leaf(&value),
+            value,
+            size: 1,
+            left,
+            right,
+        })));
+        node.update();
+        node
+    }
+
     // 参照系メソッド
     fn color(&self) -> &Color {
         &self.0.as_ref().unwrap().color
@@ -39,22 +71,30 @@ impl<T: Ord> Node<T> {
         &mut self.0.as_mut().unwrap().value
     }
 
-    fn left(&self) -> &Node<T> {
+    fn left(&self) -> &Node<T, A> {
         &self.0.as_ref().unwrap().left
     }
 
-    fn left_mut(&mut self) -> &mut Node<T> {
+    fn left_mut(&mut self) -> &mut Node<T, A> {
         &mut self.0.as_mut().unwrap().left
     }
 
-    fn right(&self) -> &Node<T> {
+    fn right(&self) -> &Node<T, A> {
         &self.0.as_ref().unwrap().right
     }
 
-    fn right_mut(&mut self) -> &mut Node<T> {
+    fn right_mut(&mut self) -> &mut Node<T, A> {
         &mut self.0.as_mut().unwrap().right
     }
 
+    fn size(&self) -> usize {
+        self.0.as_ref().map_or(0, |b| b.size)
+    }
+
+    fn summary(&self) -> Option<&A::Summary> {
+        self.0.as_ref().map(|b| &b.summary)
+    }
+
     // 判定系メソッド
     fn is_null(&self) -> bool {
         self.0.is_none()
@@ -77,6 +117,20 @@ impl<T: Ord> Node<T> {
         Node(self.0.take())
     }
 
+    // 子の情報から部分木の大きさと集約を計算し直す
+    // 子や値が変わったら必ず呼ぶ
+    fn update(&mut self) {
+        let size = 1 + self.left().size() + self.right().size();
+        let summary = A::combine_opt(
+            A::combine_opt(self.left().summary().cloned(), Some(A::leaf(self.value()))),
+            self.right().summary().cloned(),
+        )
+        .unwrap();
+        let b = self.0.as_mut().unwrap();
+        b.size = size;
+        b.summary = summary;
+    }
+
     // 色交換
     fn swap_colors(&mut self, other: &mut Self) {
         mem::swap(self.color_mut(), other.color_mut())
@@ -90,7 +144,9 @@ impl<T: Ord> Node<T> {
         let mut w = self.take();
         let mut u = w.right_mut().take();
         *w.right_mut() = u.left_mut().take();
+        w.update();
         *u.left_mut() = w;
+        u.update();
         *self = u;
     }
 
@@ -102,7 +158,9 @@ impl<T: Ord> Node<T> {
         let mut u = self.take();
         let mut w = u.left_mut().take();
         *u.left_mut() = w.right_mut().take();
+        u.update();
         *w.right_mut() = u;
+        w.update();
         *self = w;
     }
 
@@ -123,38 +181,153 @@ impl<T: Ord> Node<T> {
     }
 
     // 検索
-    fn contains(&self, value: &T) -> bool {
-        if self.is_null() {
-            false
-        } else {
-            match value.cmp(self.value()) {
-                Less => self.left().contains(value),
-                Equal => true,
-                Greater => self.right().contains(value),
+    // f は探している値と節の値との比較結果を返す
+    fn get<F: Fn(&T) -> Ordering>(&self, f: F) -> Option<&T> {
+        let mut node = self;
+        while !node.is_null() {
+            match f(node.value()) {
+                Less => node = node.left(),
+                Equal => return Some(node.value()),
+                Greater => node = node.right(),
+            }
+        }
+        None
+    }
+
+    fn get_mut<F: Fn(&T) -> Ordering>(&mut self, f: F) -> Option<&mut T> {
+        let mut node = self;
+        while !node.is_null() {
+            match f(node.value()) {
+                Less => node = node.left_mut(),
+                Equal => return Some(node.value_mut()),
+                Greater => node = node.right_mut(),
+            }
+        }
+        None
+    }
+
+    // pred が false, ..., false, true, ..., true と並ぶとき、最初に true となる値
+    fn find_first<P: Fn(&T) -> bool>(&self, pred: P) -> Option<&T> {
+        let mut node = self;
+        let mut found = None;
+        while !node.is_null() {
+            if pred(node.value()) {
+                found = Some(node.value());
+                node = node.left();
+            } else {
+                node = node.right();
+            }
+        }
+        found
+    }
+
+    // pred が true, ..., true, false, ..., false と並ぶとき、最後に true となる値
+    fn find_last<P: Fn(&T) -> bool>(&self, pred: P) -> Option<&T> {
+        let mut node = self;
+        let mut found = None;
+        while !node.is_null() {
+            if pred(node.value()) {
+                found = Some(node.value());
+                node = node.right();
+            } else {
+                node = node.left();
             }
         }
+        found
+    }
+
+    // 小さい方から k 番目 (0-indexed) の値
+    fn select(&self, mut k: usize) -> Option<&T> {
+        let mut node = self;
+        while !node.is_null() {
+            let l = node.left().size();
+            match k.cmp(&l) {
+                Less => node = node.left(),
+                Equal => return Some(node.value()),
+                Greater => {
+                    k -= l + 1;
+                    node = node.right();
+                }
+            }
+        }
+        None
+    }
+
+    fn select_mut(&mut self, mut k: usize) -> Option<&mut T> {
+        let mut node = self;
+        while !node.is_null() {
+            let l = node.left().size();
+            match k.cmp(&l) {
+                Less => node = node.left_mut(),
+                Equal => return Some(node.value_mut()),
+                Greater => {
+                    k -= l + 1;
+                    node = node.right_mut();
+                }
+            }
+        }
+        None
+    }
+
+    // pred が true, ..., true, false, ..., false と並ぶとき、true となる値の個数
+    fn count_while<P: Fn(&T) -> bool>(&self, pred: P) -> usize {
+        let mut node = self;
+        let mut count = 0;
+        while !node.is_null() {
+            if pred(node.value()) {
+                count += node.left().size() + 1;
+                node = node.right();
+            } else {
+                node = node.left();
+            }
+        }
+        count
+    }
+
+    // f で探したときの順位
+    // 見つかれば Ok(その値の順位)、見つからなければ Err(挿入されるべき順位)
+    fn position<F: Fn(&T) -> Ordering>(&self, f: F) -> Result<usize, usize> {
+        let mut node = self;
+        let mut rank = 0;
+        while !node.is_null() {
+            match f(node.value()) {
+                Less => node = node.left(),
+                Equal => return Ok(rank + node.left().size()),
+                Greater => {
+                    rank += node.left().size() + 1;
+                    node = node.right();
+                }
+            }
+        }
+        Err(rank)
     }
 
     // 挿入
-    fn insert(&mut self, value: T) -> bool {
+    // 挿入した値の順位を返し、等しい値が既にあれば挿入せずに (その値の順位, 値) を返す
+    // 順位は回転で変わらないので、挿入後に select_mut で値を引ける
+    fn insert<F: Fn(&T, &T) -> Ordering>(
+        &mut self,
+        value: T,
+        cmp: &F,
+    ) -> Result<usize, (usize, T)> {
         if self.is_null() {
-            *self = Node(Some(Box::new(NodeInner {
-                color: Red,
-                value,
-                left: Node(None),
-                right: Node(None),
-            })));
-            true
+            *self = Node::new(Red, value, Node(None), Node(None));
+            Ok(0)
         } else {
-            let changed = match value.cmp(self.value()) {
-                Less => self.left_mut().insert(value),
-                Equal => false,
-                Greater => self.right_mut().insert(value),
+            let offset = self.left().size() + 1;
+            let result = match cmp(&value, self.value()) {
+                Less => self.left_mut().insert(value, cmp),
+                Equal => Err((offset - 1, value)),
+                Greater => match self.right_mut().insert(value, cmp) {
+                    Ok(rank) => Ok(offset + rank),
+                    Err((rank, value)) => Err((offset + rank, value)),
+                },
             };
-            if changed {
+            self.update();
+            if result.is_ok() {
                 self.insert_fixup();
             }
-            changed
+            result
         }
     }
 
@@ -180,18 +353,36 @@ impl<T: Ord> Node<T> {
     }
 
     // 削除
-    // (削除されたかどうか, double black かどうか)
-    fn remove(&mut self, value: &T) -> (bool, bool) {
+    // (削除された値, double black かどうか)
+    fn remove<F: Fn(&T) -> Ordering>(&mut self, f: &F) -> (Option<T>, bool) {
+        self.remove_by(&mut |node: &Self| f(node.value()))
+    }
+
+    // 小さい方から k 番目 (0-indexed) の値の削除
+    fn remove_at(&mut self, mut k: usize) -> (Option<T>, bool) {
+        self.remove_by(&mut |node: &Self| {
+            let l = node.left().size();
+            let ord = k.cmp(&l);
+            if ord == Greater {
+                k -= l + 1;
+            }
+            ord
+        })
+    }
+
+    // f は経路上の節に上から順に一度ずつ呼ばれ、削除する値がどちらにあるかを返す
+    fn remove_by<F: FnMut(&Self) -> Ordering>(&mut self, f: &mut F) -> (Option<T>, bool) {
         if self.is_null() {
-            (false, false)
+            (None, false)
         } else {
-            let (change, mut double) = match value.cmp(self.value()) {
+            let (removed, mut double) = match f(self) {
                 Less => {
-                    let (changed, mut double) = self.left_mut().remove(value);
+                    let (removed, mut double) = self.left_mut().remove_by(f);
+                    self.update();
                     if double {
                         double = self.remove_fixup_left();
                     }
-                    (changed, double)
+                    (removed, double)
                 }
                 Equal => {
                     // 右子が空なら左子に差し替え
@@ -199,22 +390,25 @@ impl<T: Ord> Node<T> {
                     if self.right().is_null() {
                         let n = *self.0.take().unwrap();
                         *self = n.left;
-                        (true, matches!(n.color, Black))
+                        (Some(n.value), matches!(n.color, Black))
                     } else {
                         let (value, mut double) = self.right_mut().remove_min();
-                        *self.value_mut() = value;
+                        self.update();
+                        let removed = mem::replace(self.value_mut(), value);
+                        self.update();
                         if double {
                             double = self.remove_fixup_right();
                         }
-                        (true, double)
+                        (Some(removed), double)
                     }
                 }
                 Greater => {
-                    let (changed, mut double) = self.right_mut().remove(value);
+                    let (removed, mut double) = self.right_mut().remove_by(f);
+                    self.update();
                     if double {
                         double = self.remove_fixup_right();
                     }
-                    (changed, double)
+                    (removed, double)
                 }
             };
             // 左傾性を保つ
@@ -225,7 +419,7 @@ impl<T: Ord> Node<T> {
                 *self.color_mut() = Black;
                 double = false;
             }
-            (change, double)
+            (removed, double)
         }
     }
 
@@ -244,6 +438,7 @@ impl<T: Ord> Node<T> {
             (n.value, double)
         } else {
             let (value, mut double) = self.left_mut().remove_min();
+            self.update();
             if double {
                 double = self.remove_fixup_left();
             }
@@ -255,6 +450,33 @@ impl<T: Ord> Node<T> {
         }
     }
 
+    // 最大値の削除
+    // (取り除かれた値, self が double black であるかどうか)
+    fn remove_max(&mut self) -> (T, bool) {
+        if self.right().is_null() {
+            // 右が空なら左は空か赤い葉なので、取り除かれた節が黒 ⇔ double
+            let n = *self.0.take().unwrap();
+            *self = n.left;
+            let mut double = matches!(n.color, Black);
+            if double && self.is_red() {
+                *self.color_mut() = Black;
+                double = false;
+            }
+            (n.value, double)
+        } else {
+            let (value, mut double) = self.right_mut().remove_max();
+            self.update();
+            if double {
+                double = self.remove_fixup_right();
+            }
+            // 左傾性を保つ
+            if !self.is_null() && self.left().is_black() && self.right().is_red() {
+                self.flip_left();
+            }
+            (value, double)
+        }
+    }
+
     // 左部分木のノード削除に伴う修正
     fn remove_fixup_left(&mut self) -> bool {
         // Case 2
@@ -334,6 +556,9 @@ impl<T: Ord> Node<T> {
             if self.left().is_black() && self.right().is_red() {
                 return Err("Property 9.5 (left-leaning) not satisfied.");
             }
+            if self.size() != 1 + self.left().size() + self.right().size() {
+                return Err("Subtree size not maintained.");
+            }
             let l = self.left().check()?;
             let r = self.right().check()?;
             if l != r {
@@ -348,25 +573,48 @@ impl<T: Ord> Node<T> {
     }
 }
 
-pub struct RedBlackTree<T: Ord> {
-    root: Node<T>,
+// 順序は C で与える
+pub struct RedBlackTree<T, A: Augment<T> = (), C: Compare<T> = Natural> {
+    root: Node<T, A>,
     len: usize,
+    cmp: C,
 }
 
 impl<T: Ord> RedBlackTree<T> {
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, C: Compare<T>> RedBlackTree<T, (), C> {
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::empty(cmp)
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T>> RedBlackTree<T, A, C> {
+    fn empty(cmp: C) -> Self {
         Self {
             root: Node(None),
             len: 0,
+            cmp,
         }
     }
 
-    pub fn contains(&self, value: &T) -> bool {
-        self.root.contains(value)
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        self.root
+            .get(|x| self.cmp.compare(value, x.borrow()))
+            .is_some()
     }
 
     pub fn insert(&mut self, value: T) -> bool {
-        let changed = self.root.insert(value);
+        let cmp = &self.cmp;
+        let changed = self.root.insert(value, &|a, b| cmp.compare(a, b)).is_ok();
         *self.root.color_mut() = Black;
         if changed {
             self.len += 1;
@@ -374,27 +622,177 @@ impl<T: Ord> RedBlackTree<T> {
         changed
     }
 
-    pub fn remove(&mut self, value: &T) -> bool {
-        let (changed, _double) = self.root.remove(value);
-        if changed {
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        let cmp = &self.cmp;
+        let (removed, _double) = self.root.remove(&|x| cmp.compare(value, x.borrow()));
+        if removed.is_some() {
             self.len -= 1;
         }
-        changed
+        removed.is_some()
     }
 
     pub fn len(&self) -> usize {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.root.find_first(|_| true)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.root.find_last(|_| true)
+    }
+
+    // value 以上の最小の値
+    // 本では find() となっている
+    pub fn ceiling<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        self.root
+            .find_first(|x| self.cmp.compare(x.borrow(), value) != Less)
+    }
+
+    // value 以下の最大の値
+    pub fn floor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        self.root
+            .find_last(|x| self.cmp.compare(x.borrow(), value) != Greater)
+    }
+
+    // value より大きい最小の値
+    pub fn successor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        self.root
+            .find_first(|x| self.cmp.compare(x.borrow(), value) == Greater)
+    }
+
+    // value より小さい最大の値
+    pub fn predecessor<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        self.root
+            .find_last(|x| self.cmp.compare(x.borrow(), value) == Less)
+    }
+
+    // 小さい方から k 番目 (0-indexed) の値
+    pub fn select(&self, k: usize) -> Option<&T> {
+        self.root.select(k)
+    }
+
+    // value より小さい値の個数
+    pub fn rank<Q>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        self.root
+            .count_while(|x| self.cmp.compare(x.borrow(), value) == Less)
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        if self.root.is_null() {
+            return None;
+        }
+        let (value, _double) = self.root.remove_min();
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        if self.root.is_null() {
+            return None;
+        }
+        let (value, _double) = self.root.remove_max();
+        self.len -= 1;
+        Some(value)
+    }
+
+    // 昇順
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        Iter::new(&self.root, self.len)
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T, A>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+        R: RangeBounds<Q>,
+    {
+        Range::new(&self.root, range, &self.cmp)
+    }
+
     pub fn check(&self) -> Result<(), &str> {
         self.root.check()?;
         Ok(())
     }
 }
 
-impl<T: Ord + fmt::Debug> fmt::Debug for RedBlackTree<T> {
+// 集約の種類は型で指定する
+// let tree: RedBlackTree<i32, Sum> = RedBlackTree::default();
+impl<T, A: Augment<T>, C: Compare<T> + Default> Default for RedBlackTree<T, A, C> {
+    fn default() -> Self {
+        Self::empty(C::default())
+    }
+}
+
+impl<T: fmt::Debug, A: Augment<T>, C: Compare<T>> RedBlackTree<T, A, C> {
+    // Graphviz の DOT 形式
+    pub fn to_dot(&self) -> String {
+        self.dot(false)
+    }
+
+    // null の葉も描く
+    pub fn to_dot_with_nil(&self) -> String {
+        self.dot(true)
+    }
+
+    fn dot(&self, show_nil: bool) -> String {
+        let mut dot = Dot::new("RedBlackTree", show_nil);
+        let mut stack = vec![(None, "", &self.root)];
+        while let Some((parent, label, node)) = stack.pop() {
+            if node.is_null() {
+                let id = dot.nil();
+                dot.edge(parent, id, label);
+            } else {
+                let color = if node.is_red() { "red" } else { "black" };
+                let id = Some(dot.node(node.value(), Some(color)));
+                dot.edge(parent, id, label);
+                stack.push((id, "R", node.right()));
+                stack.push((id, "L", node.left()));
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T: fmt::Debug, A: Augment<T>, C: Compare<T>> fmt::Debug for RedBlackTree<T, A, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fn show<T: Ord + fmt::Debug>(node: &Node<T>) -> (usize, usize, Vec<String>) {
+        fn show<T: fmt::Debug, A: Augment<T>>(node: &Node<T, A>) -> (usize, usize, Vec<String>) {
             if node.is_null() {
                 (0, 0, vec![])
             } else {
@@ -438,8 +836,10 @@ impl<T: Ord + fmt::Debug> fmt::Debug for RedBlackTree<T> {
 mod tests {
     use super::RedBlackTree;
     use rand::seq::SliceRandom;
+    use std::ops::Bound;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison, clippy::needless_range_loop)]
     fn test_red_black_tree() {
         let mut tree = RedBlackTree::new();
         let mut v = (0..100).collect::<Vec<_>>();
@@ -468,4 +868,93 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_red_black_tree_select_rank() {
+        let mut tree = RedBlackTree::new();
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            tree.insert(x * 2);
+            tree.check().unwrap();
+        }
+        for k in 0..100 {
+            assert_eq!(tree.select(k), Some(&(k as i32 * 2)));
+        }
+        assert_eq!(tree.select(100), None);
+        for x in -1..201 {
+            assert_eq!(tree.rank(&x), ((x + 1) / 2) as usize);
+        }
+        v.shuffle(&mut rng);
+        for &x in &v[..50] {
+            tree.remove(&(x * 2));
+            tree.check().unwrap();
+        }
+        let rest = tree.iter().copied().collect::<Vec<_>>();
+        for (k, x) in rest.iter().enumerate() {
+            assert_eq!(tree.select(k), Some(x));
+            assert_eq!(tree.rank(x), k);
+        }
+        while let Some(x) = tree.pop_last() {
+            tree.check().unwrap();
+            assert_eq!(tree.rank(&x), tree.len());
+        }
+    }
+
+    #[test]
+    fn test_red_black_tree_borrow() {
+        let mut tree = RedBlackTree::new();
+        for s in &["b", "d", "f"] {
+            tree.insert(s.to_string());
+        }
+        assert!(tree.contains("d"));
+        assert!(!tree.contains("e"));
+        assert_eq!(tree.ceiling("c").map(String::as_str), Some("d"));
+        assert_eq!(tree.predecessor("b"), None);
+        assert_eq!(tree.rank("e"), 2);
+        let range = (Bound::Included("c"), Bound::Excluded("f"));
+        assert!(tree.range::<str, _>(range).eq(&["d"]));
+        assert!(tree.remove("b"));
+        tree.check().unwrap();
+        assert_eq!(tree.len(), 2);
+    }
+
+    #[test]
+    fn test_red_black_tree_neighbors() {
+        let mut tree = RedBlackTree::new();
+        assert_eq!(tree.first(), None);
+        assert_eq!(tree.ceiling(&0), None);
+        assert_eq!(tree.pop_first(), None);
+        assert_eq!(tree.pop_last(), None);
+        let mut v = (0..50).map(|x| x * 2).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            tree.insert(x);
+        }
+        assert_eq!(tree.first(), Some(&0));
+        assert_eq!(tree.last(), Some(&98));
+        v.sort();
+        for i in -1..101 {
+            assert_eq!(tree.ceiling(&i), v.iter().find(|&&x| x >= i));
+            assert_eq!(tree.floor(&i), v.iter().rev().find(|&&x| x <= i));
+            assert_eq!(tree.successor(&i), v.iter().find(|&&x| x > i));
+            assert_eq!(tree.predecessor(&i), v.iter().rev().find(|&&x| x < i));
+        }
+
+        let (mut lo, mut hi) = (0, 98);
+        while !tree.is_empty() {
+            if rand::random() {
+                assert_eq!(tree.pop_first(), Some(lo));
+                lo += 2;
+            } else {
+                assert_eq!(tree.pop_last(), Some(hi));
+                hi -= 2;
+            }
+            tree.check().unwrap();
+            assert_eq!(tree.len(), tree.iter().count());
+        }
+        assert_eq!(tree.pop_first(), None);
+    }
 }
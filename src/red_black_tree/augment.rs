@@ -0,0 +1,259 @@
+use super::{Compare, Node, RedBlackTree};
+use std::borrow::Borrow;
+use std::cmp::Ordering::{Greater, Less};
+use std::ops::{Add, Bound, RangeBounds};
+
+// 部分木の値の集約
+// combine は結合的である必要がある (可換でなくてもよい)
+pub trait Augment<T> {
+    type Summary: Clone;
+
+    fn leaf(value: &T) -> Self::Summary;
+
+    fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+
+    // 空の部分木を None として扱う
+    fn combine_opt(
+        left: Option<Self::Summary>,
+        right: Option<Self::Summary>,
+    ) -> Option<Self::Summary> {
+        match (left, right) {
+            (None, right) => right,
+            (left, None) => left,
+            (Some(left), Some(right)) => Some(Self::combine(&left, &right)),
+        }
+    }
+}
+
+// 集約なし
+impl<T> Augment<T> for () {
+    type Summary = ();
+
+    fn leaf(_value: &T) {}
+
+    fn combine(_left: &(), _right: &()) {}
+}
+
+// 総和
+pub struct Sum;
+
+impl<T: Clone + Add<Output = T>> Augment<T> for Sum {
+    type Summary = T;
+
+    fn leaf(value: &T) -> T {
+        value.clone()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        left.clone() + right.clone()
+    }
+}
+
+impl<T, A: Augment<T>> Node<T, A> {
+    // above が false, ..., false, true, ..., true と並ぶとき、true となる値の集約
+    fn fold_suffix<P: Fn(&T) -> bool>(&self, above: P) -> Option<A::Summary> {
+        let mut node = self;
+        let mut acc = None;
+        while !node.is_null() {
+            if above(node.value()) {
+                let right =
+                    A::combine_opt(Some(A::leaf(node.value())), node.right().summary().cloned());
+                acc = A::combine_opt(right, acc);
+                node = node.left();
+            } else {
+                node = node.right();
+            }
+        }
+        acc
+    }
+
+    // below が true, ..., true, false, ..., false と並ぶとき、true となる値の集約
+    fn fold_prefix<P: Fn(&T) -> bool>(&self, below: P) -> Option<A::Summary> {
+        let mut node = self;
+        let mut acc = None;
+        while !node.is_null() {
+            if below(node.value()) {
+                let left =
+                    A::combine_opt(node.left().summary().cloned(), Some(A::leaf(node.value())));
+                acc = A::combine_opt(acc, left);
+                node = node.right();
+            } else {
+                node = node.left();
+            }
+        }
+        acc
+    }
+}
+
+impl<T, A: Augment<T>> Node<T, A>
+where
+    A::Summary: PartialEq,
+{
+    // 各節の集約が、子の集約と自身の値から計算し直したものと一致すること
+    fn check_summary(&self) -> Result<(), &'static str> {
+        if self.is_null() {
+            return Ok(());
+        }
+        self.left().check_summary()?;
+        self.right().check_summary()?;
+        let summary = A::combine_opt(
+            A::combine_opt(self.left().summary().cloned(), Some(A::leaf(self.value()))),
+            self.right().summary().cloned(),
+        );
+        if summary.as_ref() != self.summary() {
+            return Err("Subtree summary not maintained.");
+        }
+        Ok(())
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T>> RedBlackTree<T, A, C>
+where
+    A::Summary: PartialEq,
+{
+    // check() に加えて集約も確かめる
+    // 集約を比較できる場合に限る
+    pub fn check_summary(&self) -> Result<(), &str> {
+        self.check()?;
+        self.root.check_summary()?;
+        Ok(())
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T>> RedBlackTree<T, A, C> {
+    // range に含まれる値を昇順に combine したもの
+    // 範囲が空なら None
+    pub fn fold<Q, R>(&self, range: R) -> Option<A::Summary>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+        R: RangeBounds<Q>,
+    {
+        let above = |x: &T| match range.start_bound() {
+            Bound::Included(start) => self.cmp.compare(x.borrow(), start) != Less,
+            Bound::Excluded(start) => self.cmp.compare(x.borrow(), start) == Greater,
+            Bound::Unbounded => true,
+        };
+        let below = |x: &T| match range.end_bound() {
+            Bound::Included(end) => self.cmp.compare(x.borrow(), end) != Greater,
+            Bound::Excluded(end) => self.cmp.compare(x.borrow(), end) == Less,
+            Bound::Unbounded => true,
+        };
+        // 両端が分かれる節まで降りる
+        let mut node = &self.root;
+        while !node.is_null() {
+            if !above(node.value()) {
+                node = node.right();
+            } else if !below(node.value()) {
+                node = node.left();
+            } else {
+                let left = node.left().fold_suffix(above);
+                let right = node.right().fold_prefix(below);
+                return A::combine_opt(A::combine_opt(left, Some(A::leaf(node.value()))), right);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RedBlackTree;
+    use super::{Augment, Sum};
+    use rand::seq::SliceRandom;
+
+    // 非可換な集約として、値を昇順に並べた列
+    struct Concat;
+
+    impl Augment<i32> for Concat {
+        type Summary = Vec<i32>;
+
+        fn leaf(value: &i32) -> Vec<i32> {
+            vec![*value]
+        }
+
+        fn combine(left: &Vec<i32>, right: &Vec<i32>) -> Vec<i32> {
+            left.iter().chain(right).copied().collect()
+        }
+    }
+
+    struct Gcd;
+
+    impl Augment<u64> for Gcd {
+        type Summary = u64;
+
+        fn leaf(value: &u64) -> u64 {
+            *value
+        }
+
+        fn combine(left: &u64, right: &u64) -> u64 {
+            let (mut a, mut b) = (*left, *right);
+            while b != 0 {
+                a %= b;
+                std::mem::swap(&mut a, &mut b);
+            }
+            a
+        }
+    }
+
+    #[test]
+    fn test_red_black_tree_fold() {
+        let mut tree: RedBlackTree<i32, Sum> = RedBlackTree::default();
+        let mut concat: RedBlackTree<i32, Concat> = RedBlackTree::default();
+        let mut v = (0..60).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            tree.insert(x);
+            concat.insert(x);
+            tree.check_summary().unwrap();
+            concat.check_summary().unwrap();
+        }
+        for &x in &v[..20] {
+            tree.remove(&x);
+            concat.remove(&x);
+            tree.check_summary().unwrap();
+            concat.check_summary().unwrap();
+        }
+        let rest = tree.iter().copied().collect::<Vec<_>>();
+        for lo in -1..62 {
+            for hi in lo..62 {
+                let expected = rest
+                    .iter()
+                    .filter(|&&x| lo <= x && x < hi)
+                    .copied()
+                    .collect::<Vec<_>>();
+                let sum = if expected.is_empty() {
+                    None
+                } else {
+                    Some(expected.iter().sum())
+                };
+                assert_eq!(tree.fold(lo..hi), sum);
+                let concat_expected = if expected.is_empty() {
+                    None
+                } else {
+                    Some(expected)
+                };
+                assert_eq!(concat.fold(lo..hi), concat_expected);
+            }
+        }
+        assert_eq!(tree.fold(..), Some(rest.iter().sum()));
+        while tree.pop_first().is_some() {
+            tree.check_summary().unwrap();
+            assert_eq!(tree.fold(..), tree.iter().copied().reduce(|a, b| a + b));
+        }
+    }
+
+    #[test]
+    fn test_red_black_tree_fold_gcd() {
+        let mut tree: RedBlackTree<u64, Gcd> = RedBlackTree::default();
+        for x in &[12, 18, 30, 42, 7, 49, 35] {
+            tree.insert(*x);
+        }
+        assert_eq!(tree.fold(12..35), Some(6));
+        assert_eq!(tree.fold(..), Some(1));
+        assert_eq!(tree.fold(35..=49), Some(7));
+        assert_eq!(tree.fold(50..), None);
+    }
+}
@@ -0,0 +1,156 @@
+use super::Color::{Black, Red};
+use super::{Augment, Compare, Node, RedBlackTree};
+use std::cmp::Ordering::{Equal, Greater};
+use std::iter::FromIterator;
+
+// 昇順に並んだ n 個の値から、中央値を根とする平衡な木を作る
+// 兄弟の大きさの差は高々 1 で、一つの子しか持たない節ではそれが左子となる
+// 葉の深さは h または h + 1 となるので、深さ h の節を赤くすれば黒高さが揃う
+// 節の作り方は木の実装ごとに異なるので、nil と node で受け取る (node の bool は赤かどうか)
+// red_black_tree_v1 からも使う
+pub(crate) fn build_balanced<T, N, I, E, F>(iter: &mut I, n: usize, nil: &E, node: &F) -> N
+where
+    I: Iterator<Item = T>,
+    E: Fn() -> N,
+    F: Fn(bool, T, N, N) -> N,
+{
+    fn build<T, N, I, E, F>(iter: &mut I, n: usize, depth: usize, h: usize, nil: &E, node: &F) -> N
+    where
+        I: Iterator<Item = T>,
+        E: Fn() -> N,
+        F: Fn(bool, T, N, N) -> N,
+    {
+        if n == 0 {
+            return nil();
+        }
+        let left = build(iter, n / 2, depth + 1, h, nil, node);
+        let value = iter.next().unwrap();
+        let right = build(iter, n - n / 2 - 1, depth + 1, h, nil, node);
+        node(depth == h, value, left, right)
+    }
+
+    // 完全に埋まる段の数
+    let mut h = 0;
+    while 1 << (h + 1) <= n + 1 {
+        h += 1;
+    }
+    build(iter, n, 0, h, nil, node)
+}
+
+impl<T, A: Augment<T>, C: Compare<T>> RedBlackTree<T, A, C> {
+    // 昇順に並んだ値から O(n) で木を作る
+    // 等しい値が続く場合は最初のものを残す
+    // 並んでいなければ整列してから作る (O(n log n))
+    pub fn from_sorted_iter_with<I: IntoIterator<Item = T>>(iter: I, cmp: C) -> Self {
+        let mut v = iter.into_iter().collect::<Vec<_>>();
+        if v.windows(2).any(|w| cmp.compare(&w[0], &w[1]) == Greater) {
+            return Self::from_vec(v, cmp);
+        }
+        v.dedup_by(|a, b| cmp.compare(a, b) == Equal);
+        Self::from_sorted_vec(v, cmp)
+    }
+
+    // 整列してから構築する
+    // 安定ソートは既に整列済みの連続部分を検出するので、整列済みの入力なら O(n)
+    pub(super) fn from_vec(mut v: Vec<T>, cmp: C) -> Self {
+        v.sort_by(|a, b| cmp.compare(a, b));
+        v.dedup_by(|a, b| cmp.compare(a, b) == Equal);
+        Self::from_sorted_vec(v, cmp)
+    }
+
+    pub(super) fn from_sorted_vec(v: Vec<T>, cmp: C) -> Self {
+        let n = v.len();
+        let root = build_balanced(
+            &mut v.into_iter(),
+            n,
+            &|| Node(None),
+            &|red, value, l, r| Node::new(if red { Red } else { Black }, value, l, r),
+        );
+        Self { root, len: n, cmp }
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T> + Default> RedBlackTree<T, A, C> {
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted_iter_with(iter, C::default())
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T> + Default> FromIterator<T> for RedBlackTree<T, A, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect(), C::default())
+    }
+}
+
+// 追加分で木を作ってから append する
+impl<T, A: Augment<T>, C: Compare<T> + Clone> Extend<T> for RedBlackTree<T, A, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // append は等しい値があれば other 側を残すので、元の木を other にして
+        // BTreeSet::extend と同じく self の値を残す
+        let mut other = Self::from_vec(iter.into_iter().collect(), self.cmp.clone());
+        std::mem::swap(self, &mut other);
+        self.append(&mut other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Key, RedBlackTree};
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn test_red_black_tree_from_sorted_iter() {
+        for k in 0..13 {
+            for n in (1usize << k).saturating_sub(2)..(1 << k) + 2 {
+                let tree: RedBlackTree<_> = RedBlackTree::from_sorted_iter(0..n);
+                tree.check().unwrap();
+                assert_eq!(tree.len(), n);
+                assert!(tree.iter().copied().eq(0..n));
+                for i in 0..n {
+                    assert_eq!(tree.select(i), Some(&i));
+                }
+            }
+        }
+        let tree: RedBlackTree<_> = RedBlackTree::from_sorted_iter(vec![1, 1, 2, 3, 3, 3, 4]);
+        tree.check().unwrap();
+        assert!(tree.iter().copied().eq(1..5));
+        // 並んでいなくても正しい木になる
+        let tree: RedBlackTree<_> = RedBlackTree::from_sorted_iter(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+        tree.check().unwrap();
+        assert!(tree.iter().copied().eq(vec![1, 2, 3, 4, 5, 6, 9]));
+        assert!(tree.contains(&9));
+    }
+
+    #[test]
+    fn test_red_black_tree_from_iter_extend() {
+        let mut v = (0..1000).map(|x| x / 2).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        let mut tree = v[..600].iter().copied().collect::<RedBlackTree<_>>();
+        tree.check().unwrap();
+        tree.extend(v[600..].iter().copied());
+        tree.check().unwrap();
+        assert_eq!(tree.len(), 500);
+        assert!(tree.iter().copied().eq(0..500));
+        // 挿入・削除が引き続き正しく動くこと
+        for i in 0..250 {
+            assert!(tree.remove(&(i * 2)));
+            tree.check().unwrap();
+        }
+        assert!(tree.insert(1000));
+        tree.check().unwrap();
+    }
+
+    #[test]
+    fn test_red_black_tree_extend_keeps_existing() {
+        let mut tree = RedBlackTree::with_comparator(Key::new(|p: &(i32, &str)| p.0));
+        tree.insert((1, "first"));
+        tree.insert((2, "second"));
+        tree.extend(vec![(1, "third"), (3, "fourth"), (3, "fifth")]);
+        tree.check().unwrap();
+        assert_eq!(
+            tree.iter().copied().collect::<Vec<_>>(),
+            vec![(1, "first"), (2, "second"), (3, "fourth")]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use super::{Augment, Compare, Node, NodeInner, RedBlackTree};
+use std::borrow::Borrow;
+use std::cmp::Ordering::{Greater, Less};
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+
+// 昇順に辿るときのスタック
+// 節をスタックに積み、左子を辿り続ける
+fn push_left<'a, T, A: Augment<T>>(stack: &mut Vec<&'a Node<T, A>>, mut node: &'a Node<T, A>) {
+    while !node.is_null() {
+        stack.push(node);
+        node = node.left();
+    }
+}
+
+// 降順に辿るときのスタック
+fn push_right<'a, T, A: Augment<T>>(stack: &mut Vec<&'a Node<T, A>>, mut node: &'a Node<T, A>) {
+    while !node.is_null() {
+        stack.push(node);
+        node = node.right();
+    }
+}
+
+// 前からと後ろからの 2 本のスタックで辿る
+// 残っている要素は常に front の先頭から back の先頭までの区間になっている
+pub struct Range<'a, T, A: Augment<T> = ()> {
+    front: Vec<&'a Node<T, A>>,
+    back: Vec<&'a Node<T, A>>,
+}
+
+impl<'a, T, A: Augment<T>> Range<'a, T, A> {
+    pub(super) fn new<Q, R, C>(root: &'a Node<T, A>, range: R, cmp: &C) -> Self
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+        C: Compare<Q>,
+    {
+        let mut front = vec![];
+        let mut node = root;
+        while !node.is_null() {
+            let above = match range.start_bound() {
+                Bound::Included(start) => cmp.compare(node.value().borrow(), start) != Less,
+                Bound::Excluded(start) => cmp.compare(node.value().borrow(), start) == Greater,
+                Bound::Unbounded => true,
+            };
+            if above {
+                front.push(node);
+                node = node.left();
+            } else {
+                node = node.right();
+            }
+        }
+        let mut back = vec![];
+        let mut node = root;
+        while !node.is_null() {
+            let below = match range.end_bound() {
+                Bound::Included(end) => cmp.compare(node.value().borrow(), end) != Greater,
+                Bound::Excluded(end) => cmp.compare(node.value().borrow(), end) == Less,
+                Bound::Unbounded => true,
+            };
+            if below {
+                back.push(node);
+                node = node.right();
+            } else {
+                node = node.left();
+            }
+        }
+        let mut range = Range { front, back };
+        match (range.front.last(), range.back.last()) {
+            (Some(first), Some(last))
+                if cmp.compare(first.value().borrow(), last.value().borrow()) != Greater => {}
+            _ => {
+                range.front.clear();
+                range.back.clear();
+            }
+        }
+        range
+    }
+}
+
+impl<'a, T, A: Augment<T>> Range<'a, T, A> {
+    // 先頭と末尾が同じ節なら、それが最後の 1 要素
+    fn take_last(&mut self) -> Option<&'a T> {
+        let node = self.front.pop()?;
+        self.front.clear();
+        self.back.clear();
+        Some(node.value())
+    }
+}
+
+impl<'a, T, A: Augment<T>> Iterator for Range<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = *self.front.last()?;
+        if ptr::eq(node, *self.back.last()?) {
+            return self.take_last();
+        }
+        self.front.pop();
+        push_left(&mut self.front, node.right());
+        Some(node.value())
+    }
+}
+
+impl<'a, T, A: Augment<T>> DoubleEndedIterator for Range<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = *self.back.last()?;
+        if ptr::eq(node, *self.front.last()?) {
+            return self.take_last();
+        }
+        self.back.pop();
+        push_right(&mut self.back, node.left());
+        Some(node.value())
+    }
+}
+
+impl<'a, T, A: Augment<T>> FusedIterator for Range<'a, T, A> {}
+
+// 全体を走る Range に残りの個数を持たせたもの
+pub struct Iter<'a, T, A: Augment<T> = ()> {
+    range: Range<'a, T, A>,
+    len: usize,
+}
+
+impl<'a, T, A: Augment<T>> Iter<'a, T, A> {
+    pub(super) fn new(root: &'a Node<T, A>, len: usize) -> Self {
+        let mut front = vec![];
+        push_left(&mut front, root);
+        let mut back = vec![];
+        push_right(&mut back, root);
+        Iter {
+            range: Range { front, back },
+            len,
+        }
+    }
+}
+
+impl<'a, T, A: Augment<T>> Iterator for Iter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.range.next()?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, A: Augment<T>> DoubleEndedIterator for Iter<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let value = self.range.next_back()?;
+        self.len -= 1;
+        Some(value)
+    }
+}
+
+impl<'a, T, A: Augment<T>> ExactSizeIterator for Iter<'a, T, A> {}
+
+impl<'a, T, A: Augment<T>> FusedIterator for Iter<'a, T, A> {}
+
+// 所有権を持って昇順に辿る
+// スタックに積んだ節は左子を外してある
+pub struct IntoIter<T, A: Augment<T> = ()> {
+    stack: Vec<Box<NodeInner<T, A>>>,
+    len: usize,
+}
+
+impl<T, A: Augment<T>> IntoIter<T, A> {
+    pub(super) fn new(root: Node<T, A>, len: usize) -> Self {
+        let mut iter = IntoIter { stack: vec![], len };
+        iter.push_left(root);
+        iter
+    }
+
+    fn push_left(&mut self, mut node: Node<T, A>) {
+        while let Some(mut b) = node.0 {
+            node = b.left.take();
+            self.stack.push(b);
+        }
+    }
+}
+
+impl<T, A: Augment<T>> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let b = self.stack.pop()?;
+        let NodeInner { value, right, .. } = *b;
+        self.push_left(right);
+        self.len -= 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, A: Augment<T>> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Augment<T>> FusedIterator for IntoIter<T, A> {}
+
+impl<T, A: Augment<T>, C: Compare<T>> IntoIterator for RedBlackTree<T, A, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.root, self.len)
+    }
+}
+
+impl<'a, T, A: Augment<T>, C: Compare<T>> IntoIterator for &'a RedBlackTree<T, A, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RedBlackTree;
+    use rand::seq::SliceRandom;
+    use std::collections::BTreeSet;
+    use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+    #[test]
+    fn test_red_black_tree_iter() {
+        let mut tree = RedBlackTree::new();
+        let mut v = (0..100).map(|x| x * 2).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            tree.insert(x);
+        }
+        v.sort();
+        assert_eq!(tree.iter().len(), 100);
+        assert!(tree.iter().eq(v.iter()));
+        assert!(tree.iter().rev().eq(v.iter().rev()));
+
+        // 前後から交互に取り出す
+        let mut iter = tree.iter();
+        let mut front = vec![];
+        let mut back = vec![];
+        while let Some(&x) = iter.next() {
+            front.push(x);
+            assert_eq!(iter.len(), 100 - front.len() - back.len());
+            match iter.next_back() {
+                Some(&x) => back.push(x),
+                None => break,
+            }
+        }
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, v);
+
+        assert!((&tree).into_iter().eq(v.iter()));
+        assert_eq!(tree.into_iter().collect::<Vec<_>>(), v);
+    }
+
+    #[test]
+    fn test_red_black_tree_range() {
+        let mut tree = RedBlackTree::new();
+        let mut set = BTreeSet::new();
+        for i in 0..50 {
+            tree.insert(i * 2);
+            set.insert(i * 2);
+        }
+        let bounds = |x: i32| vec![Included(x), Excluded(x), Unbounded];
+        for lo in -2..102 {
+            for hi in lo..102 {
+                for &start in &bounds(lo) {
+                    for &end in &bounds(hi) {
+                        let range: (Bound<i32>, Bound<i32>) = (start, end);
+                        if lo == hi && matches!(range, (Excluded(_), Excluded(_))) {
+                            // BTreeSet::range は空区間 (x, x) で panic する
+                            continue;
+                        }
+                        assert!(tree.range(range).eq(set.range(range)));
+                        assert!(tree.range(range).rev().eq(set.range(range).rev()));
+                    }
+                }
+            }
+        }
+        let mut range = tree.range(10..20);
+        assert_eq!(range.next(), Some(&10));
+        assert_eq!(range.next_back(), Some(&18));
+        assert_eq!(range.next_back(), Some(&16));
+        assert_eq!(range.next(), Some(&12));
+        assert_eq!(range.next(), Some(&14));
+        assert_eq!(range.next_back(), None);
+        assert_eq!(range.next(), None);
+    }
+}
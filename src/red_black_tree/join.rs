@@ -0,0 +1,354 @@
+use super::Color::{Black, Red};
+use super::{Augment, Compare, Node, NodeInner, RedBlackTree};
+use std::borrow::Borrow;
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+
+impl<T, A: Augment<T>> Node<T, A> {
+    // 黒高さ (null は 0)
+    // 左の辺を辿って黒い節を数える
+    fn black_height(&self) -> usize {
+        let mut node = self;
+        let mut h = 0;
+        while !node.is_null() {
+            if node.is_black() {
+                h += 1;
+            }
+            node = node.left();
+        }
+        h
+    }
+
+    // 根を黒くする (黒高さが 1 増えることがある)
+    fn blacken(&mut self) {
+        if self.is_red() {
+            *self.color_mut() = Black;
+        }
+    }
+
+    fn into_inner(self) -> Option<(T, Self, Self)> {
+        self.0.map(|b| {
+            let NodeInner {
+                value, left, right, ..
+            } = *b;
+            (value, left, right)
+        })
+    }
+
+    // left の値 < pivot < right の値 であるような 2 つの木を pivot を挟んで繋ぐ
+    // 黒高さの差を h として O(h)
+    fn join(mut left: Self, pivot: T, mut right: Self) -> Self {
+        left.blacken();
+        right.blacken();
+        let hl = left.black_height();
+        let hr = right.black_height();
+        let mut node = match hl.cmp(&hr) {
+            Equal => Node::new(Black, pivot, left, right),
+            Greater => {
+                left.join_right(hl, pivot, right, hr);
+                left
+            }
+            Less => {
+                right.join_left(hr, left, hl, pivot);
+                right
+            }
+        };
+        node.blacken();
+        node
+    }
+
+    // 右の辺を黒高さ hr の黒い節まで降り、そこに赤い節 pivot を差し込む
+    // 赤い節が続くことがあるので、挿入と同じく帰りがけに修正する
+    fn join_right(&mut self, h: usize, pivot: T, right: Self, hr: usize) {
+        if self.is_black() && h == hr {
+            let left = self.take();
+            *self = Node::new(Red, pivot, left, right);
+        } else {
+            let h = if self.is_black() { h - 1 } else { h };
+            self.right_mut().join_right(h, pivot, right, hr);
+            self.update();
+            self.insert_fixup();
+        }
+    }
+
+    // 左の辺を黒高さ hl の黒い節まで降り、そこに赤い節 pivot を差し込む
+    fn join_left(&mut self, h: usize, left: Self, hl: usize, pivot: T) {
+        if self.is_black() && h == hl {
+            let right = self.take();
+            *self = Node::new(Red, pivot, left, right);
+        } else {
+            let h = if self.is_black() { h - 1 } else { h };
+            self.left_mut().join_left(h, left, hl, pivot);
+            self.update();
+            self.insert_fixup();
+        }
+    }
+
+    // pivot を挟まずに繋ぐ
+    fn join2(left: Self, mut right: Self) -> Self {
+        if right.is_null() {
+            left
+        } else {
+            let (pivot, _double) = right.remove_min();
+            Node::join(left, pivot, right)
+        }
+    }
+
+    // f が Greater となる値, Equal となる値, Less となる値 に分ける
+    fn split<F: Fn(&T) -> Ordering>(self, f: &F) -> (Self, Option<T>, Self) {
+        match self.into_inner() {
+            None => (Node(None), None, Node(None)),
+            Some((value, left, right)) => match f(&value) {
+                Less => {
+                    let (ll, found, lr) = left.split(f);
+                    (ll, found, Node::join(lr, value, right))
+                }
+                Equal => (left, Some(value), right),
+                Greater => {
+                    let (rl, found, rr) = right.split(f);
+                    (Node::join(left, value, rl), found, rr)
+                }
+            },
+        }
+    }
+
+    // 以下、other の根で self を分割して再帰的に処理し、join で繋ぐ
+    // 左右の再帰は独立なので並列化できる
+    fn union<F: Fn(&T, &T) -> Ordering>(self, other: Self, cmp: &F) -> Self {
+        if self.is_null() {
+            return other;
+        }
+        match other.into_inner() {
+            None => self,
+            Some((value, left, right)) => {
+                let (l, _, r) = self.split(&|x| cmp(&value, x));
+                let l = l.union(left, cmp);
+                let r = r.union(right, cmp);
+                Node::join(l, value, r)
+            }
+        }
+    }
+
+    fn intersection<F: Fn(&T, &T) -> Ordering>(self, other: Self, cmp: &F) -> Self {
+        if self.is_null() {
+            return self;
+        }
+        match other.into_inner() {
+            None => Node(None),
+            Some((value, left, right)) => {
+                let (l, found, r) = self.split(&|x| cmp(&value, x));
+                let l = l.intersection(left, cmp);
+                let r = r.intersection(right, cmp);
+                if found.is_some() {
+                    Node::join(l, value, r)
+                } else {
+                    Node::join2(l, r)
+                }
+            }
+        }
+    }
+
+    fn difference<F: Fn(&T, &T) -> Ordering>(self, other: Self, cmp: &F) -> Self {
+        if self.is_null() {
+            return self;
+        }
+        match other.into_inner() {
+            None => self,
+            Some((value, left, right)) => {
+                let (l, _, r) = self.split(&|x| cmp(&value, x));
+                let l = l.difference(left, cmp);
+                let r = r.difference(right, cmp);
+                Node::join2(l, r)
+            }
+        }
+    }
+
+    fn symmetric_difference<F: Fn(&T, &T) -> Ordering>(self, other: Self, cmp: &F) -> Self {
+        if self.is_null() {
+            return other;
+        }
+        match other.into_inner() {
+            None => self,
+            Some((value, left, right)) => {
+                let (l, found, r) = self.split(&|x| cmp(&value, x));
+                let l = l.symmetric_difference(left, cmp);
+                let r = r.symmetric_difference(right, cmp);
+                if found.is_some() {
+                    Node::join2(l, r)
+                } else {
+                    Node::join(l, value, r)
+                }
+            }
+        }
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T>> RedBlackTree<T, A, C> {
+    fn from_root(root: Node<T, A>, cmp: C) -> Self {
+        Self {
+            len: root.size(),
+            root,
+            cmp,
+        }
+    }
+
+    // left の値 < pivot < right の値 でなければならない
+    pub fn join(left: Self, pivot: T, right: Self) -> Self {
+        debug_assert!(left
+            .last()
+            .is_none_or(|x| left.cmp.compare(x, &pivot) == Less));
+        debug_assert!(right
+            .first()
+            .is_none_or(|x| left.cmp.compare(&pivot, x) == Less));
+        Self::from_root(Node::join(left.root, pivot, right.root), left.cmp)
+    }
+
+    // other の値をすべて self に移す
+    pub fn append(&mut self, other: &mut Self) {
+        let cmp = &self.cmp;
+        let root = self
+            .root
+            .take()
+            .union(other.root.take(), &|a, b| cmp.compare(a, b));
+        self.len = root.size();
+        self.root = root;
+        other.len = 0;
+    }
+
+    // std の BTreeSet と異なり、2 つの木を消費して新たな木を作る
+    pub fn union(self, other: Self) -> Self {
+        let cmp = self.cmp;
+        let root = self.root.union(other.root, &|a, b| cmp.compare(a, b));
+        Self::from_root(root, cmp)
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        let cmp = self.cmp;
+        let root = self
+            .root
+            .intersection(other.root, &|a, b| cmp.compare(a, b));
+        Self::from_root(root, cmp)
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        let cmp = self.cmp;
+        let root = self.root.difference(other.root, &|a, b| cmp.compare(a, b));
+        Self::from_root(root, cmp)
+    }
+
+    pub fn symmetric_difference(self, other: Self) -> Self {
+        let cmp = self.cmp;
+        let root = self
+            .root
+            .symmetric_difference(other.root, &|a, b| cmp.compare(a, b));
+        Self::from_root(root, cmp)
+    }
+}
+
+impl<T, A: Augment<T>, C: Compare<T> + Clone> RedBlackTree<T, A, C> {
+    // value 以上の値を取り出す
+    pub fn split_off<Q>(&mut self, value: &Q) -> Self
+    where
+        T: Borrow<Q>,
+        Q: ?Sized,
+        C: Compare<Q>,
+    {
+        let cmp = &self.cmp;
+        let (left, found, right) = self.root.take().split(&|x| cmp.compare(value, x.borrow()));
+        let right = match found {
+            Some(found) => Node::join(Node(None), found, right),
+            None => right,
+        };
+        self.len = left.size();
+        self.root = left;
+        Self::from_root(right, self.cmp.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RedBlackTree;
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn random_tree(rng: &mut ThreadRng, n: usize, max: i32) -> (RedBlackTree<i32>, BTreeSet<i32>) {
+        let mut tree = RedBlackTree::new();
+        let mut set = BTreeSet::new();
+        for _ in 0..n {
+            let x = rng.gen_range(0, max);
+            tree.insert(x);
+            set.insert(x);
+        }
+        (tree, set)
+    }
+
+    fn assert_same(tree: &RedBlackTree<i32>, set: &BTreeSet<i32>) {
+        tree.check().unwrap();
+        assert_eq!(tree.len(), set.len());
+        assert!(tree.iter().eq(set.iter()));
+    }
+
+    #[test]
+    fn test_red_black_tree_join_split() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(0, 100);
+            let m = rng.gen_range(0, 100);
+            let mut left = RedBlackTree::new();
+            let mut right = RedBlackTree::new();
+            for x in 0..n {
+                left.insert(x);
+            }
+            for x in 0..m {
+                right.insert(n + 1 + x);
+            }
+            let tree = RedBlackTree::join(left, n, right);
+            let set = (0..=n + m).collect::<BTreeSet<_>>();
+            assert_same(&tree, &set);
+
+            let (mut tree, mut set) = random_tree(&mut rng, 100, 200);
+            let x = rng.gen_range(-1, 201);
+            let tree_right = tree.split_off(&x);
+            let set_right = set.split_off(&x);
+            assert_same(&tree, &set);
+            assert_same(&tree_right, &set_right);
+        }
+    }
+
+    fn to_tree(set: &BTreeSet<i32>) -> RedBlackTree<i32> {
+        let mut tree = RedBlackTree::new();
+        for &x in set {
+            tree.insert(x);
+        }
+        tree
+    }
+
+    #[test]
+    fn test_red_black_tree_set_operations() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let n = rng.gen_range(0, 100);
+            let m = rng.gen_range(0, 100);
+            let (a, sa) = random_tree(&mut rng, n, 150);
+            let (b, sb) = random_tree(&mut rng, m, 150);
+            assert_same(&a.union(b), &sa.union(&sb).copied().collect());
+            assert_same(
+                &to_tree(&sa).intersection(to_tree(&sb)),
+                &sa.intersection(&sb).copied().collect(),
+            );
+            assert_same(
+                &to_tree(&sa).difference(to_tree(&sb)),
+                &sa.difference(&sb).copied().collect(),
+            );
+            assert_same(
+                &to_tree(&sa).symmetric_difference(to_tree(&sb)),
+                &sa.symmetric_difference(&sb).copied().collect(),
+            );
+
+            let mut a = to_tree(&sa);
+            let mut b = to_tree(&sb);
+            a.append(&mut b);
+            assert_same(&a, &sa.union(&sb).copied().collect());
+            assert!(b.is_empty());
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use super::Color::Black;
+use super::Node;
+use std::borrow::Borrow;
+use std::fmt;
+
+// (キー, 値) の組を Node に載せ、キーのみで比較する
+pub struct RedBlackTreeMap<K: Ord, V> {
+    pub(super) root: Node<(K, V)>,
+    pub(super) len: usize,
+}
+
+impl<K: Ord, V> RedBlackTreeMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            root: Node(None),
+            len: 0,
+        }
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root.get(|(k, _)| key.cmp(k.borrow())).map(|(_, v)| v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.root
+            .get_mut(|(k, _)| key.cmp(k.borrow()))
+            .map(|(_, v)| v)
+    }
+
+    // 既にキーがあれば値を差し替えて古い値を返す
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.root.insert((key, value), &|(a, _), (b, _)| a.cmp(b)) {
+            Ok(_) => {
+                *self.root.color_mut() = Black;
+                self.len += 1;
+                None
+            }
+            // キーは std::collections::BTreeMap と同じく元のものを残す
+            Err((rank, (_, value))) => {
+                let (_, v) = self.root.select_mut(rank).unwrap();
+                Some(std::mem::replace(v, value))
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (removed, _double) = self.root.remove(&|(k, _)| key.cmp(k.borrow()));
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed.map(|(_, v)| v)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.root.position(|(k, _)| key.cmp(k)) {
+            Ok(rank) => Entry::Occupied(OccupiedEntry { map: self, rank }),
+            Err(_) => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn check(&self) -> Result<(), &str> {
+        self.root.check()?;
+        Ok(())
+    }
+}
+
+impl<K: Ord, V> Default for RedBlackTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for RedBlackTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn walk<'a, K, V>(node: &'a Node<(K, V)>, entries: &mut Vec<(&'a K, &'a V)>) {
+            if !node.is_null() {
+                walk(node.left(), entries);
+                let (k, v) = node.value();
+                entries.push((k, v));
+                walk(node.right(), entries);
+            }
+        }
+        let mut entries = vec![];
+        walk(&self.root, &mut entries);
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+// std::collections::btree_map::Entry に倣ったもの
+// キーの比較は entry での一度だけで、以降は順位で値を引く
+// 間に他の操作は入らないので、Occupied の順位は変わらない
+pub enum Entry<'a, K: Ord, V> {
+    Vacant(VacantEntry<'a, K, V>),
+    Occupied(OccupiedEntry<'a, K, V>),
+}
+
+pub struct VacantEntry<'a, K: Ord, V> {
+    map: &'a mut RedBlackTreeMap<K, V>,
+    key: K,
+}
+
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    map: &'a mut RedBlackTreeMap<K, V>,
+    rank: usize,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+        }
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let VacantEntry { map, key } = self;
+        let rank = match map.root.insert((key, value), &|(a, _), (b, _)| a.cmp(b)) {
+            Ok(rank) => rank,
+            Err(_) => unreachable!(),
+        };
+        *map.root.color_mut() = Black;
+        map.len += 1;
+        &mut map.root.select_mut(rank).unwrap().1
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.map.root.select(self.rank).unwrap().0
+    }
+
+    pub fn get(&self) -> &V {
+        &self.map.root.select(self.rank).unwrap().1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.root.select_mut(self.rank).unwrap().1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.root.select_mut(self.rank).unwrap().1
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        let (removed, _double) = self.map.root.remove_at(self.rank);
+        self.map.len -= 1;
+        removed.unwrap().1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, RedBlackTreeMap};
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn test_red_black_tree_map() {
+        let mut map = RedBlackTreeMap::new();
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            assert_eq!(map.insert(x, x * 10), None);
+            map.check().unwrap();
+        }
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.insert(3, 0), Some(30));
+        assert_eq!(map.get(&3), Some(&0));
+        *map.get_mut(&3).unwrap() = 30;
+        for i in 0..100 {
+            assert_eq!(map.get(&i), Some(&(i * 10)));
+        }
+        assert_eq!(map.get(&100), None);
+        v.shuffle(&mut rng);
+        for &x in &v {
+            if x % 2 == 0 {
+                assert_eq!(map.remove(&x), Some(x * 10));
+                map.check().unwrap();
+            }
+        }
+        assert_eq!(map.remove(&0), None);
+        assert_eq!(map.len(), 50);
+        for i in 0..100 {
+            assert_eq!(map.contains_key(&i), i % 2 == 1);
+        }
+        dbg!(&map);
+    }
+
+    #[test]
+    fn test_red_black_tree_map_entry() {
+        let mut map = RedBlackTreeMap::new();
+        for c in "abracadabra".chars() {
+            *map.entry(c).or_insert(0) += 1;
+            map.check().unwrap();
+        }
+        assert_eq!(map.get(&'a'), Some(&5));
+        assert_eq!(map.get(&'b'), Some(&2));
+        assert_eq!(map.get(&'r'), Some(&2));
+        assert_eq!(map.get(&'c'), Some(&1));
+        assert_eq!(map.get(&'d'), Some(&1));
+        assert_eq!(map.len(), 5);
+
+        map.entry('a').and_modify(|x| *x *= 10).or_insert(0);
+        map.entry('z').and_modify(|x| *x *= 10).or_insert(0);
+        assert_eq!(map.get(&'a'), Some(&50));
+        assert_eq!(map.get(&'z'), Some(&0));
+        assert_eq!(*map.entry('y').or_insert_with(|| 7), 7);
+        assert_eq!(map.len(), 7);
+        map.check().unwrap();
+
+        match map.entry('r') {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.key(), &'r');
+                assert_eq!(entry.insert(20), 2);
+                assert_eq!(entry.get(), &20);
+                assert_eq!(entry.remove(), 20);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        assert_eq!(map.get(&'r'), None);
+        assert_eq!(map.len(), 6);
+        map.check().unwrap();
+
+        let mut map = RedBlackTreeMap::new();
+        map.insert("one".to_string(), 1);
+        map.insert("two".to_string(), 2);
+        assert_eq!(map.get("two"), Some(&2));
+        assert!(map.contains_key("one"));
+        assert_eq!(map.remove("one"), Some(1));
+        assert_eq!(map.get("one"), None);
+    }
+}
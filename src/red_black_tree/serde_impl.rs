@@ -0,0 +1,129 @@
+// 値の昇順の列として読み書きする
+// 読み込みは整列済みの入力なら O(n) で木を作る
+use super::iter::Iter;
+use super::{Augment, Compare, RedBlackTree, RedBlackTreeMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::mem;
+
+impl<T: Serialize, A: Augment<T>, C: Compare<T>> Serialize for RedBlackTree<T, A, C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T, A, C> Deserialize<'de> for RedBlackTree<T, A, C>
+where
+    T: Deserialize<'de>,
+    A: Augment<T>,
+    C: Compare<T> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Vec::deserialize(deserializer)?;
+        Ok(Self::from_vec(v, C::default()))
+    }
+}
+
+// (キー, 値) の組の列
+impl<K: Ord + Serialize, V: Serialize> Serialize for RedBlackTreeMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(Iter::new(&self.root, self.len))
+    }
+}
+
+// 同じキーが複数あれば、BTreeMap と同じく後のものを残す
+impl<'de, K, V> Deserialize<'de> for RedBlackTreeMap<K, V>
+where
+    K: Ord + Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut v: Vec<(K, V)> = Vec::deserialize(deserializer)?;
+        v.sort_by(|a, b| a.0.cmp(&b.0));
+        v.dedup_by(|later, kept| {
+            let same = later.0 == kept.0;
+            if same {
+                mem::swap(later, kept);
+            }
+            same
+        });
+        let tree = RedBlackTree::<(K, V), (), _>::from_sorted_vec(v, |a: &(K, V), b: &(K, V)| {
+            a.0.cmp(&b.0)
+        });
+        Ok(RedBlackTreeMap {
+            root: tree.root,
+            len: tree.len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Natural, RedBlackTree, RedBlackTreeMap, Rev};
+    use serde::de::value::{Error, SeqDeserializer};
+    use serde::Deserialize;
+    use serde_test::{assert_ser_tokens, Token};
+
+    fn from_seq<'de, T: Deserialize<'de>>(v: Vec<i32>) -> T {
+        T::deserialize(SeqDeserializer::<_, Error>::new(v.into_iter())).unwrap()
+    }
+
+    #[test]
+    fn test_red_black_tree_serde() {
+        let tree: RedBlackTree<_> = vec![3, 1, 2].into_iter().collect();
+        assert_ser_tokens(
+            &tree,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let tree: RedBlackTree<i32> = from_seq((0..1000).collect());
+        tree.check().unwrap();
+        assert!(tree.iter().copied().eq(0..1000));
+        // 整列されていない入力や重複も受け付ける
+        let tree: RedBlackTree<i32> = from_seq(vec![5, 3, 5, 1, 3]);
+        tree.check().unwrap();
+        assert!(tree.iter().copied().eq(vec![1, 3, 5]));
+        let tree: RedBlackTree<i32, (), Rev<Natural>> = from_seq(vec![1, 3, 2]);
+        tree.check().unwrap();
+        assert!(tree.iter().copied().eq(vec![3, 2, 1]));
+    }
+
+    #[test]
+    fn test_red_black_tree_map_serde() {
+        let mut map = RedBlackTreeMap::new();
+        map.insert(2, 'b');
+        map.insert(1, 'a');
+        assert_ser_tokens(
+            &map,
+            &[
+                Token::Seq { len: Some(2) },
+                Token::Tuple { len: 2 },
+                Token::I32(1),
+                Token::Char('a'),
+                Token::TupleEnd,
+                Token::Tuple { len: 2 },
+                Token::I32(2),
+                Token::Char('b'),
+                Token::TupleEnd,
+                Token::SeqEnd,
+            ],
+        );
+
+        // 組は長さ 2 の列として読める
+        let pairs = vec![(2, 24), (1, 1), (2, 4), (3, 9)]
+            .into_iter()
+            .map(|(k, v)| SeqDeserializer::<_, Error>::new(vec![k, v].into_iter()));
+        let map = RedBlackTreeMap::<i32, i32>::deserialize(SeqDeserializer::<_, Error>::new(pairs))
+            .unwrap();
+        map.check().unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(&1), Some(&1));
+        assert_eq!(map.get(&2), Some(&4));
+        assert_eq!(map.get(&3), Some(&9));
+    }
+}
@@ -1,32 +1,83 @@
+use crate::dot::Dot;
+use crate::red_black_tree::build_balanced;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
 use std::cmp::{
     Ord,
     Ordering::{Equal, Greater, Less},
 };
 use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::Arc;
 use Node::{Black, Nil, Red};
 
-enum Node<T: Ord> {
+// 部分木を共有するためのポインタの種類
+// 更新では根からの経路だけを作り直し、それ以外の部分木は古い版と共有する
+pub trait PointerFamily {
+    type Pointer<U>: Clone + Deref<Target = U>;
+
+    fn new<U>(value: U) -> Self::Pointer<U>;
+}
+
+pub struct RcPointer;
+
+impl PointerFamily for RcPointer {
+    type Pointer<U> = Rc<U>;
+
+    fn new<U>(value: U) -> Rc<U> {
+        Rc::new(value)
+    }
+}
+
+// スレッド間で版を共有する場合
+pub struct ArcPointer;
+
+impl PointerFamily for ArcPointer {
+    type Pointer<U> = Arc<U>;
+
+    fn new<U>(value: U) -> Arc<U> {
+        Arc::new(value)
+    }
+}
+
+type Link<T, P> = <P as PointerFamily>::Pointer<Node<T, P>>;
+
+// 値もポインタ越しに持つので、経路の作り直しで T を複製しなくてよい
+type Value<T, P> = <P as PointerFamily>::Pointer<T>;
+
+enum Node<T: Ord, P: PointerFamily> {
     Nil,
-    Red(T, Box<Node<T>>, Box<Node<T>>),
-    Black(T, Box<Node<T>>, Box<Node<T>>),
+    Red(Value<T, P>, Link<T, P>, Link<T, P>),
+    Black(Value<T, P>, Link<T, P>, Link<T, P>),
 }
 
-pub struct RedBlackTree<T: Ord> {
-    root: Box<Node<T>>,
+// clone() は O(1) で、その時点の版を保存できる
+pub struct RedBlackTree<T: Ord, P: PointerFamily = RcPointer> {
+    root: Link<T, P>,
+    len: usize,
 }
 
+pub type ArcRedBlackTree<T> = RedBlackTree<T, ArcPointer>;
+
 impl<T: Ord> RedBlackTree<T> {
     pub fn new() -> Self {
-        Self {
-            root: Box::new(Nil),
-        }
+        Self::default()
     }
+}
 
-    fn contains_inner(node: &Node<T>, value: T) -> bool {
+impl<T: Ord, P: PointerFamily> RedBlackTree<T, P> {
+    fn contains_inner<Q>(node: &Node<T, P>, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         match node {
             Nil => false,
             Red(node_value, left, right) | Black(node_value, left, right) => {
-                match value.cmp(&node_value) {
+                match value.cmp((**node_value).borrow()) {
                     Less => Self::contains_inner(left, value),
                     Equal => true,
                     Greater => Self::contains_inner(right, value),
@@ -35,104 +86,482 @@ impl<T: Ord> RedBlackTree<T> {
         }
     }
 
-    pub fn contains(&self, value: T) -> bool {
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         Self::contains_inner(&self.root, value)
     }
 
-    fn insert_inner(node: Box<Node<T>>, value: T) -> (bool, Box<Node<T>>) {
-        let nil = || Box::new(Nil);
-        let red = |value, left, right| Box::new(Red(value, left, right));
-        let black = |value, left, right| Box::new(Black(value, left, right));
+    // value 以上の最小の値
+    pub fn ceiling<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut node = &*self.root;
+        let mut found = None;
+        while let Red(node_value, left, right) | Black(node_value, left, right) = node {
+            if (**node_value).borrow() >= value {
+                found = Some(&**node_value);
+                node = left;
+            } else {
+                node = right;
+            }
+        }
+        found
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // 昇順
+    pub fn iter(&self) -> Iter<'_, T, P> {
+        let mut iter = Iter {
+            stack: vec![],
+            len: self.len,
+        };
+        iter.push_left(&self.root);
+        iter
+    }
 
-        match *node {
-            Nil => (true, red(value, nil(), nil())),
-            Red(node_value, left, right) => match value.cmp(&node_value) {
+    // 黒高さ (Nil を 1 とする)
+    fn check_inner(node: &Node<T, P>) -> Result<usize, &'static str> {
+        match node {
+            Nil => Ok(1),
+            Red(_, left, right) | Black(_, left, right) => {
+                if let Red(..) = node {
+                    if matches!(**left, Red(..)) || matches!(**right, Red(..)) {
+                        return Err("Property 9.4 (no-red-edge) not satisfied.");
+                    }
+                }
+                let l = Self::check_inner(left)?;
+                let r = Self::check_inner(right)?;
+                if l != r {
+                    return Err("Property 9.3 (black-height) not satisfied.");
+                }
+                if let Red(..) = node {
+                    Ok(l)
+                } else {
+                    Ok(l + 1)
+                }
+            }
+        }
+    }
+
+    pub fn check(&self) -> Result<(), &str> {
+        Self::check_inner(&self.root)?;
+        Ok(())
+    }
+
+    // 昇順に並んだ値から O(n) で木を作る
+    // 等しい値が続く場合は最初のものを残す
+    // 並んでいなければ整列してから作る (O(n log n))
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = iter.into_iter().collect::<Vec<_>>();
+        if v.windows(2).any(|w| w[0] > w[1]) {
+            // 安定ソートなので、等しい値は最初のものが残る
+            v.sort();
+        }
+        v.dedup();
+        let n = v.len();
+        let root = build_balanced(
+            &mut v.into_iter(),
+            n,
+            &|| P::new(Nil),
+            &|red, value, l, r| {
+                let value = P::new(value);
+                P::new(if red {
+                    Red(value, l, r)
+                } else {
+                    Black(value, l, r)
+                })
+            },
+        );
+        Self { root, len: n }
+    }
+}
+
+// 経路上の節は作り直すが、値はポインタを複製して共有する
+impl<T: Ord, P: PointerFamily> RedBlackTree<T, P> {
+    fn insert_inner(node: &Link<T, P>, value: T) -> (bool, Link<T, P>) {
+        let nil = || P::new(Nil);
+        let red = |value, left, right| P::new(Red(value, left, right));
+        let black = |value, left, right| P::new(Black(value, left, right));
+
+        match &**node {
+            Nil => (true, red(P::new(value), nil(), nil())),
+            Red(node_value, left, right) => match value.cmp(node_value) {
                 Less => {
                     let (changed, left) = Self::insert_inner(left, value);
-                    (changed, red(node_value, left, right))
+                    if !changed {
+                        return (false, node.clone());
+                    }
+                    (true, red(node_value.clone(), left, right.clone()))
                 }
-                Equal => (false, red(node_value, left, right)),
+                Equal => (false, node.clone()),
                 Greater => {
                     let (changed, right) = Self::insert_inner(right, value);
-                    (changed, red(node_value, left, right))
+                    if !changed {
+                        return (false, node.clone());
+                    }
+                    (true, red(node_value.clone(), left.clone(), right))
                 }
             },
-            Black(node_value, left, right) => match value.cmp(&node_value) {
+            Black(node_value, left, right) => match value.cmp(node_value) {
                 Less => {
                     let (changed, left) = Self::insert_inner(left, value);
                     if !changed {
-                        return (false, black(node_value, left, right));
+                        return (false, node.clone());
                     }
-                    let (v, l, r) = (node_value, left, right);
-                    if let Red(lv, ll, lr) = *l {
-                        if let Red(llv, lll, llr) = *ll {
-                            (true, red(lv, black(llv, lll, llr), black(v, lr, r)))
-                        } else if let Red(lrv, lrl, lrr) = *lr {
-                            (true, red(lrv, black(lv, ll, lrl), black(v, lrr, r)))
-                        } else {
-                            (true, black(v, red(lv, ll, lr), r))
+                    let (v, l, r) = (node_value.clone(), left, right.clone());
+                    if let Red(lv, ll, lr) = &*l {
+                        if let Red(llv, lll, llr) = &**ll {
+                            let ll = black(llv.clone(), lll.clone(), llr.clone());
+                            return (true, red(lv.clone(), ll, black(v, lr.clone(), r)));
+                        } else if let Red(lrv, lrl, lrr) = &**lr {
+                            let ll = black(lv.clone(), ll.clone(), lrl.clone());
+                            return (true, red(lrv.clone(), ll, black(v, lrr.clone(), r)));
                         }
-                    } else {
-                        (true, black(v, l, r))
                     }
+                    (true, black(v, l, r))
                 }
-                Equal => (false, black(node_value, left, right)),
+                Equal => (false, node.clone()),
                 Greater => {
                     let (changed, right) = Self::insert_inner(right, value);
                     if !changed {
-                        return (false, black(node_value, left, right));
+                        return (false, node.clone());
                     }
-                    let (v, l, r) = (node_value, left, right);
-                    if let Red(rv, rl, rr) = *r {
-                        if let Red(rlv, rll, rlr) = *rl {
-                            (true, red(rlv, black(v, l, rll), black(rv, rlr, rr)))
-                        } else if let Red(rrv, rrl, rrr) = *rr {
-                            (true, red(rv, black(v, l, rl), black(rrv, rrl, rrr)))
-                        } else {
-                            (true, black(v, l, red(rv, rl, rr)))
+                    let (v, l, r) = (node_value.clone(), left.clone(), right);
+                    if let Red(rv, rl, rr) = &*r {
+                        if let Red(rlv, rll, rlr) = &**rl {
+                            let rr = black(rv.clone(), rlr.clone(), rr.clone());
+                            return (true, red(rlv.clone(), black(v, l, rll.clone()), rr));
+                        } else if let Red(rrv, rrl, rrr) = &**rr {
+                            let rr = black(rrv.clone(), rrl.clone(), rrr.clone());
+                            return (true, red(rv.clone(), black(v, l, rl.clone()), rr));
                         }
-                    } else {
-                        (true, black(v, l, r))
                     }
+                    (true, black(v, l, r))
                 }
             },
         }
     }
 
+    // 他の版には影響しない
     pub fn insert(&mut self, value: T) -> bool {
-        let root = std::mem::replace(&mut self.root, Box::new(Nil));
-        let (changed, root) = Self::insert_inner(root, value);
+        let (changed, root) = Self::insert_inner(&self.root, value);
         if !changed {
-            self.root = root;
             return false;
         }
-        match *root {
+        self.root = match &*root {
             Nil => unreachable!(),
+            Red(node_value, left, right) => {
+                P::new(Black(node_value.clone(), left.clone(), right.clone()))
+            }
+            Black(..) => root,
+        };
+        self.len += 1;
+        true
+    }
+
+    // 以下、削除は Kahrs の方法による
+    // 部分木の黒高さが 1 減ったものを関数の間で受け渡し、balance_left/balance_right で直す
+
+    fn red(value: Value<T, P>, left: Link<T, P>, right: Link<T, P>) -> Link<T, P> {
+        P::new(Red(value, left, right))
+    }
+
+    fn black(value: Value<T, P>, left: Link<T, P>, right: Link<T, P>) -> Link<T, P> {
+        P::new(Black(value, left, right))
+    }
+
+    // 黒い節を赤くして黒高さを 1 減らす
+    fn sub1(node: &Link<T, P>) -> Link<T, P> {
+        match &**node {
+            Black(v, l, r) => Self::red(v.clone(), l.clone(), r.clone()),
+            _ => unreachable!("invariance violation"),
+        }
+    }
+
+    // 挿入の balance に、両方の子が赤い場合を加えたもの
+    fn balance(l: Link<T, P>, v: Value<T, P>, r: Link<T, P>) -> Link<T, P> {
+        if let (Red(lv, ll, lr), Red(rv, rl, rr)) = (&*l, &*r) {
+            let l = Self::black(lv.clone(), ll.clone(), lr.clone());
+            let r = Self::black(rv.clone(), rl.clone(), rr.clone());
+            return Self::red(v, l, r);
+        }
+        if let Red(lv, ll, lr) = &*l {
+            if let Red(llv, lll, llr) = &**ll {
+                let ll = Self::black(llv.clone(), lll.clone(), llr.clone());
+                return Self::red(lv.clone(), ll, Self::black(v, lr.clone(), r));
+            } else if let Red(lrv, lrl, lrr) = &**lr {
+                let ll = Self::black(lv.clone(), ll.clone(), lrl.clone());
+                return Self::red(lrv.clone(), ll, Self::black(v, lrr.clone(), r));
+            }
+        }
+        if let Red(rv, rl, rr) = &*r {
+            if let Red(rrv, rrl, rrr) = &**rr {
+                let rr = Self::black(rrv.clone(), rrl.clone(), rrr.clone());
+                return Self::red(rv.clone(), Self::black(v, l, rl.clone()), rr);
+            } else if let Red(rlv, rll, rlr) = &**rl {
+                let rr = Self::black(rv.clone(), rlr.clone(), rr.clone());
+                return Self::red(rlv.clone(), Self::black(v, l, rll.clone()), rr);
+            }
+        }
+        Self::black(v, l, r)
+    }
+
+    // 左部分木の黒高さが 1 低いときの修正
+    fn balance_left(l: Link<T, P>, v: Value<T, P>, r: Link<T, P>) -> Link<T, P> {
+        if let Red(lv, ll, lr) = &*l {
+            let l = Self::black(lv.clone(), ll.clone(), lr.clone());
+            return Self::red(v, l, r);
+        }
+        match &*r {
+            Black(rv, rl, rr) => {
+                let r = Self::red(rv.clone(), rl.clone(), rr.clone());
+                Self::balance(l, v, r)
+            }
+            Red(rv, rl, rr) => match &**rl {
+                Black(rlv, rll, rlr) => {
+                    let l = Self::black(v, l, rll.clone());
+                    let r = Self::balance(rlr.clone(), rv.clone(), Self::sub1(rr));
+                    Self::red(rlv.clone(), l, r)
+                }
+                _ => unreachable!("invariance violation"),
+            },
+            Nil => unreachable!("invariance violation"),
+        }
+    }
+
+    // 右部分木の黒高さが 1 低いときの修正
+    fn balance_right(l: Link<T, P>, v: Value<T, P>, r: Link<T, P>) -> Link<T, P> {
+        if let Red(rv, rl, rr) = &*r {
+            let r = Self::black(rv.clone(), rl.clone(), rr.clone());
+            return Self::red(v, l, r);
+        }
+        match &*l {
+            Black(lv, ll, lr) => {
+                let l = Self::red(lv.clone(), ll.clone(), lr.clone());
+                Self::balance(l, v, r)
+            }
+            Red(lv, ll, lr) => match &**lr {
+                Black(lrv, lrl, lrr) => {
+                    let l = Self::balance(Self::sub1(ll), lv.clone(), lrl.clone());
+                    let r = Self::black(v, lrr.clone(), r);
+                    Self::red(lrv.clone(), l, r)
+                }
+                _ => unreachable!("invariance violation"),
+            },
+            Nil => unreachable!("invariance violation"),
+        }
+    }
+
+    // 削除された節の左右の部分木を繋ぐ
+    fn append(l: &Link<T, P>, r: &Link<T, P>) -> Link<T, P> {
+        match (&**l, &**r) {
+            (Nil, _) => r.clone(),
+            (_, Nil) => l.clone(),
+            (Red(lv, ll, lr), Red(rv, rl, rr)) => {
+                let m = Self::append(lr, rl);
+                if let Red(mv, ml, mr) = &*m {
+                    let l = Self::red(lv.clone(), ll.clone(), ml.clone());
+                    let r = Self::red(rv.clone(), mr.clone(), rr.clone());
+                    Self::red(mv.clone(), l, r)
+                } else {
+                    let r = Self::red(rv.clone(), m, rr.clone());
+                    Self::red(lv.clone(), ll.clone(), r)
+                }
+            }
+            (Black(lv, ll, lr), Black(rv, rl, rr)) => {
+                let m = Self::append(lr, rl);
+                if let Red(mv, ml, mr) = &*m {
+                    let l = Self::black(lv.clone(), ll.clone(), ml.clone());
+                    let r = Self::black(rv.clone(), mr.clone(), rr.clone());
+                    Self::red(mv.clone(), l, r)
+                } else {
+                    let r = Self::black(rv.clone(), m, rr.clone());
+                    Self::balance_left(ll.clone(), lv.clone(), r)
+                }
+            }
+            (_, Red(rv, rl, rr)) => Self::red(rv.clone(), Self::append(l, rl), rr.clone()),
+            (Red(lv, ll, lr), _) => Self::red(lv.clone(), ll.clone(), Self::append(lr, r)),
+        }
+    }
+
+    // 値が無ければ None
+    fn remove_inner<Q>(node: &Link<T, P>, value: &Q) -> Option<Link<T, P>>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &**node {
+            Nil => None,
             Red(node_value, left, right) | Black(node_value, left, right) => {
-                self.root = Box::new(Black(node_value, left, right));
+                match value.cmp((**node_value).borrow()) {
+                    Less => {
+                        let l = Self::remove_inner(left, value)?;
+                        let (v, r) = (node_value.clone(), right.clone());
+                        if let Black(..) = **left {
+                            Some(Self::balance_left(l, v, r))
+                        } else {
+                            Some(Self::red(v, l, r))
+                        }
+                    }
+                    Equal => Some(Self::append(left, right)),
+                    Greater => {
+                        let r = Self::remove_inner(right, value)?;
+                        let (v, l) = (node_value.clone(), left.clone());
+                        if let Black(..) = **right {
+                            Some(Self::balance_right(l, v, r))
+                        } else {
+                            Some(Self::red(v, l, r))
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // 他の版には影響しない
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match Self::remove_inner(&self.root, value) {
+            None => false,
+            Some(root) => {
+                self.root = match &*root {
+                    Red(node_value, left, right) => {
+                        Self::black(node_value.clone(), left.clone(), right.clone())
+                    }
+                    _ => root,
+                };
+                self.len -= 1;
                 true
             }
         }
     }
 }
 
-impl<T: Ord + fmt::Debug> fmt::Debug for RedBlackTree<T> {
+impl<T: Ord, P: PointerFamily> Clone for RedBlackTree<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+// ArcRedBlackTree は default() で作る
+impl<T: Ord, P: PointerFamily> Default for RedBlackTree<T, P> {
+    fn default() -> Self {
+        Self {
+            root: P::new(Nil),
+            len: 0,
+        }
+    }
+}
+
+impl<T: Ord, P: PointerFamily> FromIterator<T> for RedBlackTree<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_sorted_iter(iter)
+    }
+}
+
+pub struct Iter<'a, T: Ord, P: PointerFamily> {
+    stack: Vec<&'a Node<T, P>>,
+    len: usize,
+}
+
+impl<'a, T: Ord, P: PointerFamily> Iter<'a, T, P> {
+    fn push_left(&mut self, mut node: &'a Node<T, P>) {
+        while let Red(_, left, _) | Black(_, left, _) = node {
+            self.stack.push(node);
+            node = left;
+        }
+    }
+}
+
+impl<'a, T: Ord, P: PointerFamily> Iterator for Iter<'a, T, P> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.stack.pop()? {
+            Nil => unreachable!(),
+            Red(value, _, right) | Black(value, _, right) => {
+                self.push_left(right);
+                self.len -= 1;
+                Some(value)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T: Ord, P: PointerFamily> ExactSizeIterator for Iter<'a, T, P> {}
+
+impl<T: Ord + fmt::Debug, P: PointerFamily> RedBlackTree<T, P> {
+    // Graphviz の DOT 形式
+    pub fn to_dot(&self) -> String {
+        self.dot(false)
+    }
+
+    // null の葉も描く
+    pub fn to_dot_with_nil(&self) -> String {
+        self.dot(true)
+    }
+
+    fn dot(&self, show_nil: bool) -> String {
+        let mut dot = Dot::new("RedBlackTree", show_nil);
+        let mut stack = vec![(None, "", &*self.root)];
+        while let Some((parent, label, node)) = stack.pop() {
+            match node {
+                Nil => {
+                    let id = dot.nil();
+                    dot.edge(parent, id, label);
+                }
+                Red(value, left, right) | Black(value, left, right) => {
+                    let color = if let Red(..) = node { "red" } else { "black" };
+                    let id = Some(dot.node(&**value, Some(color)));
+                    dot.edge(parent, id, label);
+                    stack.push((id, "R", right));
+                    stack.push((id, "L", left));
+                }
+            }
+        }
+        dot.finish()
+    }
+}
+
+impl<T: Ord + fmt::Debug, P: PointerFamily> fmt::Debug for RedBlackTree<T, P> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "RedBlackTree {{")?;
         let mut stack = vec![];
-        stack.push((0, self.root.as_ref()));
+        stack.push((0, &*self.root));
         while let Some((depth, node)) = stack.pop() {
             match node {
                 Nil => (),
                 Red(value, left, right) => {
                     write!(f, "{}", "    ".repeat(1 + depth))?;
-                    writeln!(f, "\x1b[31m{:?}\x1b[m", value)?;
+                    writeln!(f, "\x1b[31m{:?}\x1b[m", &**value)?;
                     stack.push((depth + 1, right));
                     stack.push((depth + 1, left));
                 }
                 Black(value, left, right) => {
                     write!(f, "{}", "    ".repeat(1 + depth))?;
-                    writeln!(f, "{:?}", value)?;
+                    writeln!(f, "{:?}", &**value)?;
                     stack.push((depth + 1, right));
                     stack.push((depth + 1, left));
                 }
@@ -142,11 +571,34 @@ impl<T: Ord + fmt::Debug> fmt::Debug for RedBlackTree<T> {
     }
 }
 
+// 値の昇順の列として読み書きする
+#[cfg(feature = "serde")]
+impl<T: Ord + Serialize, P: PointerFamily> Serialize for RedBlackTree<T, P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, P> Deserialize<'de> for RedBlackTree<T, P>
+where
+    T: Ord + Deserialize<'de>,
+    P: PointerFamily,
+{
+    // 整列済みの入力なら O(n)
+    // 等しい値が続く場合は、insert と同じく最初のものを残す
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from_sorted_iter(Vec::deserialize(deserializer)?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::RedBlackTree;
+    use super::{ArcRedBlackTree, RedBlackTree};
+    use rand::seq::SliceRandom;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_red_black_tree() {
         let mut tree = RedBlackTree::new();
         for i in 0..100 {
@@ -156,8 +608,163 @@ mod tests {
             }
         }
         for i in 0..100 {
-            assert_eq!(tree.contains(i), i % 2 == 0);
+            assert_eq!(tree.contains(&i), i % 2 == 0);
         }
         dbg!(&tree);
     }
+
+    #[test]
+    fn test_red_black_tree_borrow() {
+        let mut tree = RedBlackTree::new();
+        for s in &["foo", "bar", "baz"] {
+            tree.insert(s.to_string());
+        }
+        assert!(tree.contains("bar"));
+        assert!(!tree.contains("qux"));
+    }
+
+    #[test]
+    fn test_red_black_tree_persistent() {
+        let mut versions = vec![RedBlackTree::new()];
+        for i in 0..100 {
+            let mut tree = versions.last().unwrap().clone();
+            let i = (i % 10) * 10 + (i / 10);
+            assert!(tree.insert(i));
+            assert!(!tree.insert(i));
+            versions.push(tree);
+        }
+        // 古い版は更新の影響を受けない
+        for (k, tree) in versions.iter().enumerate() {
+            for i in 0..100 {
+                let j = (i % 10) * 10 + (i / 10);
+                assert_eq!(tree.contains(&j), i < k);
+            }
+        }
+    }
+
+    #[test]
+    fn test_red_black_tree_arc() {
+        let mut tree: ArcRedBlackTree<i32> = ArcRedBlackTree::default();
+        for i in 0..50 {
+            tree.insert(i * 2);
+        }
+        let snapshot = tree.clone();
+        let handle = std::thread::spawn(move || (0..100).filter(|i| snapshot.contains(i)).count());
+        for i in 0..50 {
+            tree.insert(i * 2 + 1);
+        }
+        assert_eq!(handle.join().unwrap(), 50);
+        assert!((0..100).all(|i| tree.contains(&i)));
+    }
+
+    #[test]
+    fn test_red_black_tree_remove() {
+        let mut tree = RedBlackTree::new();
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            if x % 2 == 0 {
+                assert!(tree.insert(x));
+                tree.check().unwrap();
+            }
+        }
+        for i in 0..100 {
+            assert_eq!(tree.contains(&i), i % 2 == 0);
+        }
+        let snapshot = tree.clone();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            if x % 2 == 0 {
+                assert!(tree.remove(&x));
+                tree.check().unwrap();
+                assert!(!tree.contains(&x));
+            } else {
+                assert!(!tree.remove(&x));
+            }
+        }
+        for i in 0..100 {
+            assert!(!tree.contains(&i));
+            assert_eq!(snapshot.contains(&i), i % 2 == 0);
+        }
+        snapshot.check().unwrap();
+    }
+
+    // Clone でない値も入れられる
+    #[test]
+    fn test_red_black_tree_not_clone() {
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+        struct Id(i32);
+
+        let mut tree = RedBlackTree::new();
+        for i in 0..10 {
+            assert!(tree.insert(Id(i)));
+        }
+        let snapshot = tree.clone();
+        assert!(tree.remove(&Id(3)));
+        tree.check().unwrap();
+        assert!(!tree.contains(&Id(3)));
+        assert!(snapshot.contains(&Id(3)));
+        assert_eq!(tree.ceiling(&Id(3)), Some(&Id(4)));
+    }
+
+    #[test]
+    fn test_red_black_tree_from_sorted_iter() {
+        for n in 0..100 {
+            let tree: RedBlackTree<_> = RedBlackTree::from_sorted_iter(0..n);
+            tree.check().unwrap();
+            assert_eq!(tree.len(), n);
+            assert!(tree.iter().copied().eq(0..n));
+        }
+        let mut v = (0..200).map(|x| x / 2).collect::<Vec<_>>();
+        v.shuffle(&mut rand::thread_rng());
+        let mut tree = v.into_iter().collect::<ArcRedBlackTree<_>>();
+        tree.check().unwrap();
+        assert_eq!(tree.len(), 100);
+        assert!(tree.iter().copied().eq(0..100));
+        // 挿入・削除が引き続き正しく動くこと
+        assert!(tree.remove(&50));
+        assert!(tree.insert(100));
+        tree.check().unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_red_black_tree_serde() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::Deserialize;
+        use serde_test::{assert_ser_tokens, Token};
+
+        let mut tree = RedBlackTree::new();
+        for &x in &[3, 1, 2] {
+            tree.insert(x);
+        }
+        assert_ser_tokens(
+            &tree,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let v = vec![5, 3, 5, 1, 3];
+        let tree =
+            ArcRedBlackTree::<i32>::deserialize(SeqDeserializer::<_, Error>::new(v.into_iter()))
+                .unwrap();
+        tree.check().unwrap();
+        assert_eq!(tree.len(), 3);
+        assert!(tree.iter().copied().eq(vec![1, 3, 5]));
+
+        // 整列済みの入力から平衡な木を作る
+        for n in 0..100 {
+            let tree =
+                RedBlackTree::<i32>::deserialize(SeqDeserializer::<_, Error>::new(0..n)).unwrap();
+            tree.check().unwrap();
+            assert_eq!(tree.len(), n as usize);
+            assert!(tree.iter().copied().eq(0..n));
+        }
+    }
 }
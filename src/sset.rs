@@ -0,0 +1,155 @@
+use crate::compare::Compare;
+use crate::red_black_tree::{self, Augment};
+use crate::red_black_tree_v1::{self, PointerFamily};
+use std::collections::{btree_set, BTreeSet};
+
+// Open Data Structures の SSet インターフェース
+// 実装を差し替えてベンチマークやテストを共通化するためのもの
+pub trait SSet<T> {
+    type Iter<'a>: Iterator<Item = &'a T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn size(&self) -> usize;
+
+    // 追加されたかどうか
+    fn add(&mut self, x: T) -> bool;
+
+    // 削除されたかどうか
+    fn remove(&mut self, x: &T) -> bool;
+
+    // x 以上の最小の値
+    fn find(&self, x: &T) -> Option<&T>;
+
+    // 昇順
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+impl<T, A: Augment<T>, C: Compare<T>> SSet<T> for red_black_tree::RedBlackTree<T, A, C> {
+    type Iter<'a>
+        = red_black_tree::Iter<'a, T, A>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn add(&mut self, x: T) -> bool {
+        self.insert(x)
+    }
+
+    fn remove(&mut self, x: &T) -> bool {
+        self.remove(x)
+    }
+
+    fn find(&self, x: &T) -> Option<&T> {
+        self.ceiling(x)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+impl<T: Ord, P: PointerFamily> SSet<T> for red_black_tree_v1::RedBlackTree<T, P> {
+    type Iter<'a>
+        = red_black_tree_v1::Iter<'a, T, P>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn add(&mut self, x: T) -> bool {
+        self.insert(x)
+    }
+
+    fn remove(&mut self, x: &T) -> bool {
+        self.remove(x)
+    }
+
+    fn find(&self, x: &T) -> Option<&T> {
+        self.ceiling(x)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+// 比較対象としての std の実装
+impl<T: Ord> SSet<T> for BTreeSet<T> {
+    type Iter<'a>
+        = btree_set::Iter<'a, T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn add(&mut self, x: T) -> bool {
+        self.insert(x)
+    }
+
+    fn remove(&mut self, x: &T) -> bool {
+        self.remove(x)
+    }
+
+    fn find(&self, x: &T) -> Option<&T> {
+        self.range(x..).next()
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SSet;
+    use crate::red_black_tree;
+    use crate::red_black_tree_v1;
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn test_sset<S: SSet<i32> + Default>() {
+        let mut s = S::default();
+        let mut v = (0..100).collect::<Vec<_>>();
+        let mut rng = rand::thread_rng();
+        v.shuffle(&mut rng);
+        for &x in &v {
+            if x % 2 == 0 {
+                assert!(s.add(x));
+            }
+        }
+        assert!(!s.add(0));
+        assert_eq!(s.size(), 50);
+        assert!(s.iter().copied().eq((0..50).map(|x| x * 2)));
+        for x in -1..100 {
+            let expected = if x < 99 { Some((x + 1) / 2 * 2) } else { None };
+            let expected = if x < 0 { Some(0) } else { expected };
+            assert_eq!(s.find(&x).copied(), expected);
+        }
+        v.shuffle(&mut rng);
+        for &x in &v {
+            assert_eq!(s.remove(&x), x % 2 == 0);
+        }
+        assert_eq!(s.size(), 0);
+        assert_eq!(s.iter().next(), None);
+    }
+
+    #[test]
+    fn test_sset_implementations() {
+        test_sset::<red_black_tree::RedBlackTree<i32>>();
+        test_sset::<red_black_tree_v1::RedBlackTree<i32>>();
+        test_sset::<red_black_tree_v1::ArcRedBlackTree<i32>>();
+        test_sset::<BTreeSet<i32>>();
+    }
+}